@@ -0,0 +1,156 @@
+use reqwest::header::{
+    HeaderMap, ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_SECURITY_POLICY,
+    SERVER, STRICT_TRANSPORT_SECURITY, VIA, X_FRAME_OPTIONS,
+};
+use reqwest::Response;
+use std::collections::{BTreeMap, BTreeSet};
+
+// Origin sent with every checked request, a permissive CORS policy reflects it.
+pub const AUDIT_ORIGIN: &str = "https://wbr-origin.example";
+
+#[derive(Default)]
+struct HostAudit {
+    responses: usize,
+    https_responses: usize,
+    missing_csp: usize,
+    missing_hsts: usize,
+    missing_xfo: usize,
+    // kind of permissive answer -> first url where it was seen
+    cors: BTreeMap<&'static str, String>,
+    cdn: BTreeSet<&'static str>,
+    servers: BTreeSet<String>,
+}
+
+// Header findings of the live check, aggregated per host.
+#[derive(Default)]
+pub struct HeaderAudit {
+    hosts: BTreeMap<String, HostAudit>,
+}
+
+impl HeaderAudit {
+    pub fn record(&mut self, url: &str, response: &Response) {
+        let headers = response.headers();
+        let host = response.url().host_str().unwrap_or("").to_string();
+        let audit = self.hosts.entry(host).or_default();
+
+        audit.responses += 1;
+        if !headers.contains_key(CONTENT_SECURITY_POLICY) {
+            audit.missing_csp += 1;
+        }
+        if !headers.contains_key(X_FRAME_OPTIONS) {
+            audit.missing_xfo += 1;
+        }
+        if response.url().scheme() == "https" {
+            audit.https_responses += 1;
+            if !headers.contains_key(STRICT_TRANSPORT_SECURITY) {
+                audit.missing_hsts += 1;
+            }
+        }
+        if let Some(kind) = permissive_cors(headers) {
+            audit.cors.entry(kind).or_insert_with(|| url.to_string());
+        }
+        audit.cdn.extend(cdn_vendors(headers));
+        for name in [SERVER.as_str(), "x-powered-by"] {
+            if let Some(value) = headers.get(name).and_then(|value| value.to_str().ok()) {
+                audit.servers.insert(value.to_string());
+            }
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let mut ret = String::new();
+        for (host, audit) in &self.hosts {
+            ret.push_str(format!("\n== Header audit of {host} ({} responses)\n", audit.responses).as_str());
+            ret.push_str(
+                format!("  missing Content-Security-Policy: {}/{}\n", audit.missing_csp, audit.responses).as_str(),
+            );
+            ret.push_str(format!("  missing X-Frame-Options: {}/{}\n", audit.missing_xfo, audit.responses).as_str());
+            if audit.https_responses > 0 {
+                ret.push_str(
+                    format!(
+                        "  missing Strict-Transport-Security: {}/{} (https)\n",
+                        audit.missing_hsts, audit.https_responses
+                    )
+                    .as_str(),
+                );
+            }
+            for (kind, url) in &audit.cors {
+                ret.push_str(format!("  permissive CORS ({kind}): {url}\n").as_str());
+            }
+            if !audit.cdn.is_empty() {
+                let cdn: Vec<&str> = audit.cdn.iter().copied().collect();
+                ret.push_str(format!("  CDN/cache: {}\n", cdn.join(", ")).as_str());
+            }
+            if !audit.servers.is_empty() {
+                let servers: Vec<&str> = audit.servers.iter().map(String::as_str).collect();
+                ret.push_str(format!("  server: {}\n", servers.join(", ")).as_str());
+            }
+        }
+        ret
+    }
+}
+
+fn permissive_cors(headers: &HeaderMap) -> Option<&'static str> {
+    let allow_origin = headers.get(ACCESS_CONTROL_ALLOW_ORIGIN)?.to_str().ok()?;
+    let credentials = headers
+        .get(ACCESS_CONTROL_ALLOW_CREDENTIALS)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    match (allow_origin, credentials) {
+        (AUDIT_ORIGIN, true) => Some("reflected origin with credentials"),
+        (AUDIT_ORIGIN, false) => Some("reflected origin"),
+        ("null", _) => Some("null origin"),
+        ("*", true) => Some("wildcard with credentials"),
+        ("*", false) => Some("wildcard"),
+        _ => None,
+    }
+}
+
+fn cdn_vendors(headers: &HeaderMap) -> Vec<&'static str> {
+    let value = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("")
+            .to_lowercase()
+    };
+    let server = value(SERVER.as_str());
+    let via = value(VIA.as_str());
+
+    let mut vendors = Vec::new();
+    if headers.contains_key("cf-ray") || headers.contains_key("cf-cache-status") || server == "cloudflare" {
+        vendors.push("Cloudflare");
+    }
+    if headers.contains_key("x-amz-cf-id") || via.contains("cloudfront") {
+        vendors.push("CloudFront");
+    }
+    if server.contains("akamai") || headers.keys().any(|name| name.as_str().starts_with("x-akamai")) {
+        vendors.push("Akamai");
+    }
+    if headers.contains_key("x-fastly-request-id") || value("x-served-by").starts_with("cache-") {
+        vendors.push("Fastly");
+    }
+    if headers.contains_key("x-azure-ref") {
+        vendors.push("Azure Front Door");
+    }
+    if headers.contains_key("x-vercel-cache") || server == "vercel" {
+        vendors.push("Vercel");
+    }
+    if headers.contains_key("x-nf-request-id") || server == "netlify" {
+        vendors.push("Netlify");
+    }
+    if headers.contains_key("x-sucuri-id") {
+        vendors.push("Sucuri");
+    }
+    if headers.contains_key("x-iinfo") || value("x-cdn").contains("incapsula") {
+        vendors.push("Imperva");
+    }
+    if via.contains("varnish") || headers.contains_key("x-varnish") {
+        vendors.push("Varnish");
+    }
+    if vendors.is_empty() && (headers.contains_key("x-cache") || headers.contains_key("x-cache-status")) {
+        vendors.push("unknown cache (X-Cache)");
+    }
+    vendors
+}
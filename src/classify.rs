@@ -0,0 +1,92 @@
+use regex::Regex;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+// gf-style built-in pattern sets, matched against the whole url.
+const BUILTIN_PATTERNS: [(&str, &str); 7] = [
+    ("xss", r"(?i)[?&](q|s|search|query|keyword|keywords|term|lang|message|msg|name|title|text|comment|callback|jsonp|html|p|page|ref|returnurl)="),
+    ("ssrf", r"(?i)([?&](url|uri|dest|redirect|path|continue|window|next|data|reference|site|html|val|validate|domain|callback|return|page|feed|host|port|to|out|view|dir|show|navigation|open|proxy|target|fetch|load|image_url|img_url|webhook)=|=(https?|ftp|gopher|file)(%3a|:))"),
+    ("redirect", r"(?i)[?&](next|url|uri|target|rurl|dest|destination|redir|redirect|redirect_uri|redirect_url|redirecturl|return|returnto|return_to|returnurl|return_url|return_path|checkout_url|continue|goto|go|forward|success_url|image_url)="),
+    ("lfi", r"(?i)([?&](file|filename|document|folder|root|path|pg|style|pdf|template|php_path|doc|include|inc|dir|download|lang|locale|conf|config|layout|module)=|\.\./|%2e%2e)"),
+    ("sqli", r"(?i)[?&](id|select|report|role|update|query|user|name|sort|sortby|order|orderby|where|search|params|process|row|view|table|from|sel|results|sleep|fetch|keyword|column|field|delete|string|number|filter|category|cat|item|pid)="),
+    ("idor", r"(?i)[?&](id|user|user_id|userid|uid|account|account_id|number|order|order_id|no|doc|doc_id|key|group|group_id|profile|profile_id|invoice|invoice_id|customer_id|member_id)=[0-9]+(&|$)"),
+    ("rce", r"(?i)[?&](cmd|exec|command|execute|ping|jump|code|reg|do|func|arg|option|load|process|step|read|function|req|feature|exe|module|payload|run|print|daemon|upload|cli|log)="),
+];
+
+// Tags urls with the categories (xss, ssrf, lfi...) of the patterns they match.
+pub struct Classifier {
+    patterns: Vec<(String, Regex)>,
+}
+
+impl Classifier {
+    // Built-in patterns plus the ones of the given JSON or TOML files.
+    pub fn new(pattern_files: &[&Path]) -> Result<Classifier, String> {
+        let mut patterns: Vec<(String, Regex)> = BUILTIN_PATTERNS
+            .iter()
+            .map(|(tag, pattern)| (tag.to_string(), Regex::new(pattern).unwrap()))
+            .collect();
+        for file in pattern_files {
+            patterns.extend(load_pattern_file(file)?);
+        }
+        Ok(Classifier { patterns })
+    }
+
+    pub fn tags(&self, url: &str) -> Vec<&str> {
+        let mut tags: Vec<&str> = Vec::new();
+        for (tag, regex) in &self.patterns {
+            if !tags.contains(&tag.as_str()) && regex.is_match(url) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+}
+
+// A pattern file is either a map of tag -> pattern(s) in JSON or TOML:
+//   {"xss": ["[?&]q=", "..."], "debug": "[?&]debug="}
+//   debug = ["[?&]debug=", "[?&]test="]
+// or a gf pattern file ({"flags": "-iE", "patterns": [...]}) tagged with its file name.
+fn load_pattern_file(file: &Path) -> Result<Vec<(String, Regex)>, String> {
+    let content = fs::read_to_string(file).map_err(|e| format!("couldn't read {}: {e}", file.display()))?;
+    let value: Value = if file.extension().map(|ext| ext == "toml").unwrap_or(false) {
+        let table: toml::Value = toml::from_str(&content).map_err(|e| format!("invalid TOML in {}: {e}", file.display()))?;
+        serde_json::to_value(table).map_err(|e| e.to_string())?
+    } else {
+        serde_json::from_str(&content).map_err(|e| format!("invalid JSON in {}: {e}", file.display()))?
+    };
+    let map = value
+        .as_object()
+        .ok_or_else(|| format!("{} must contain an object of tag -> patterns", file.display()))?;
+
+    let mut entries: Vec<(String, Value)> = Vec::new();
+    if map.contains_key("pattern") || map.contains_key("patterns") {
+        let tag = file.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        entries.push((tag, value.clone()));
+    } else {
+        entries.extend(map.iter().map(|(tag, patterns)| (tag.clone(), patterns.clone())));
+    }
+
+    let mut patterns = Vec::new();
+    for (tag, entry) in entries {
+        let (flags, list) = match &entry {
+            Value::Object(gf) => (
+                gf.get("flags").and_then(Value::as_str).unwrap_or(""),
+                gf.get("patterns").or_else(|| gf.get("pattern")).cloned().unwrap_or(Value::Null),
+            ),
+            other => ("", other.clone()),
+        };
+        let list: Vec<String> = match list {
+            Value::String(pattern) => vec![pattern],
+            Value::Array(items) => items.iter().filter_map(|item| item.as_str().map(String::from)).collect(),
+            _ => return Err(format!("patterns of '{tag}' in {} must be a string or a list", file.display())),
+        };
+        let prefix = if flags.contains('i') { "(?i)" } else { "" };
+        for pattern in list {
+            let regex = Regex::new(format!("{prefix}{pattern}").as_str())
+                .map_err(|e| format!("invalid pattern for '{tag}' in {}: {e}", file.display()))?;
+            patterns.push((tag.clone(), regex));
+        }
+    }
+    Ok(patterns)
+}
@@ -0,0 +1,51 @@
+use similar::{ChangeTag, TextDiff};
+
+// Number of changed lines shown under a result when verbose is set.
+const DIFF_PREVIEW_LINES: usize = 5;
+
+// One result line per url: verdict, similarity score and count of added/removed
+// lines between the latest archive and the live content.
+pub fn compare_contents(
+    url: &str,
+    timestamp: &str,
+    archived: &str,
+    live: &str,
+    verbose: bool,
+) -> String {
+    let diff = TextDiff::from_lines(archived, live);
+    let similarity = diff.ratio();
+
+    let mut added = 0;
+    let mut removed = 0;
+    let mut preview = String::new();
+    let mut previewed = 0;
+    for change in diff.iter_all_changes() {
+        let sign = match change.tag() {
+            ChangeTag::Insert => {
+                added += 1;
+                '+'
+            }
+            ChangeTag::Delete => {
+                removed += 1;
+                '-'
+            }
+            ChangeTag::Equal => continue,
+        };
+        if verbose && previewed < DIFF_PREVIEW_LINES && !change.value().trim().is_empty() {
+            preview.push_str(format!("  {sign} {line}\n", line = change.value().trim()).as_str());
+            previewed += 1;
+        }
+    }
+
+    let verdict = if similarity >= 0.9 {
+        "same"
+    } else if similarity >= 0.5 {
+        "changed"
+    } else {
+        "different"
+    };
+
+    format!(
+        "{url} {verdict} similarity={similarity:.2} +{added} -{removed} lines (archive {timestamp})\n{preview}"
+    )
+}
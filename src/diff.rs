@@ -0,0 +1,136 @@
+use similar::TextDiff;
+use std::collections::BTreeMap;
+
+// Unified diff between two versions of an archived url, empty when they only
+// differ by whitespace and ignore_whitespace is set.
+pub fn unified_diff(
+    url: &str,
+    old_timestamp: &str,
+    old: &str,
+    new_timestamp: &str,
+    new: &str,
+    ignore_whitespace: bool,
+) -> String {
+    let (old, new) = if ignore_whitespace {
+        (collapse_whitespace(old), collapse_whitespace(new))
+    } else {
+        (old.to_string(), new.to_string())
+    };
+    if old == new {
+        return String::new();
+    }
+    TextDiff::from_lines(&old, &new)
+        .unified_diff()
+        .context_radius(3)
+        .header(
+            format!("{url} {old_timestamp}").as_str(),
+            format!("{url} {new_timestamp}").as_str(),
+        )
+        .to_string()
+}
+
+// Trim every line, collapse inner runs of whitespace and drop blank lines.
+fn collapse_whitespace(content: &str) -> String {
+    let mut collapsed = String::new();
+    for line in content.lines() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if !words.is_empty() {
+            collapsed.push_str(words.join(" ").as_str());
+            collapsed.push('\n');
+        }
+    }
+    collapsed
+}
+
+pub enum Bound {
+    From,
+    To,
+}
+
+// Timestamp of the newest distinct version at or before a --from/--to
+// timestamp. Partial timestamps mean the start of the period for --from and
+// its end for --to. --from falls back to the first version when it is older
+// than every capture. Without timestamp: the first version for --from, the
+// latest for --to.
+pub fn find_version(archives: &BTreeMap<String, String>, timestamp: Option<&str>, bound: Bound) -> Option<String> {
+    let timestamp = match timestamp {
+        Some(timestamp) => timestamp,
+        None => {
+            return match bound {
+                Bound::From => archives.keys().next().cloned(),
+                Bound::To => archives.keys().next_back().cloned(),
+            }
+        }
+    };
+    let pad = match bound {
+        Bound::From => '0',
+        Bound::To => '9',
+    };
+    let mut limit: String = timestamp.chars().filter(char::is_ascii_digit).take(14).collect();
+    while limit.len() < 14 {
+        limit.push(pad);
+    }
+    match (archives.range(..=limit).next_back(), bound) {
+        (Some((found, _)), _) => Some(found.clone()),
+        (None, Bound::From) => archives.keys().next().cloned(),
+        (None, Bound::To) => None,
+    }
+}
+
+// Timestamps of the two versions to diff for --from/--to.
+pub fn resolve_range(
+    archives: &BTreeMap<String, String>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<(String, String), String> {
+    let from = find_version(archives, from, Bound::From).ok_or("no archive found")?;
+    let to = find_version(archives, to, Bound::To).ok_or("no archive at or before --to")?;
+    if from == to {
+        Err(format!("--from and --to resolve to the same version ({from}), nothing to diff"))
+    } else if from > to {
+        Err(format!("--from resolves to a version ({from}) newer than --to ({to})"))
+    } else {
+        Ok((from, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn archives(timestamps: &[&str]) -> BTreeMap<String, String> {
+        timestamps
+            .iter()
+            .map(|timestamp| (timestamp.to_string(), "example.com/app.js".to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn find_version_partial_year() {
+        let archives = archives(&["20180105000000", "20190315000000", "20230601000000", "20240101000000"]);
+        // in place at the start of 2019 / at the end of 2023
+        assert_eq!(find_version(&archives, Some("2019"), Bound::From).as_deref(), Some("20180105000000"));
+        assert_eq!(find_version(&archives, Some("2023"), Bound::To).as_deref(), Some("20230601000000"));
+        assert_eq!(find_version(&archives, Some("20190401"), Bound::From).as_deref(), Some("20190315000000"));
+        assert_eq!(find_version(&archives, None, Bound::From).as_deref(), Some("20180105000000"));
+        assert_eq!(find_version(&archives, None, Bound::To).as_deref(), Some("20240101000000"));
+    }
+
+    #[test]
+    fn find_version_before_first_capture() {
+        let archives = archives(&["20190315000000", "20230601000000"]);
+        assert_eq!(find_version(&archives, Some("2019"), Bound::From).as_deref(), Some("20190315000000"));
+        assert_eq!(find_version(&archives, Some("2018"), Bound::To), None);
+        assert_eq!(
+            resolve_range(&archives, Some("2019"), Some("2023")),
+            Ok(("20190315000000".to_string(), "20230601000000".to_string()))
+        );
+    }
+
+    #[test]
+    fn resolve_range_same_or_reversed() {
+        let archives = archives(&["20190315000000", "20230601000000"]);
+        assert!(resolve_range(&archives, Some("2020"), Some("2021")).unwrap_err().contains("same version"));
+        assert!(resolve_range(&archives, Some("2024"), Some("2020")).unwrap_err().contains("newer than --to"));
+    }
+}
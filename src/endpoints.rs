@@ -0,0 +1,60 @@
+use reqwest::Url;
+use std::collections::HashMap;
+
+// Group urls by endpoint template (host + path with ids replaced by {int},
+// {uuid} or {hash}), most used templates first: "template count example".
+pub fn endpoint_templates(urls: &[String]) -> String {
+    let mut templates: HashMap<String, (usize, &str)> = HashMap::new();
+    for url in urls {
+        let parsed = match Url::parse(url) {
+            Ok(parsed) => parsed,
+            Err(_) => continue,
+        };
+        let path: Vec<String> = parsed.path().split('/').map(template_segment).collect();
+        let template = format!("{host}{path}", host = parsed.host_str().unwrap_or(""), path = path.join("/"));
+        templates.entry(template).or_insert((0, url.as_str())).0 += 1;
+    }
+
+    let mut templates: Vec<(String, (usize, &str))> = templates.into_iter().collect();
+    templates.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then(a.0.cmp(&b.0)));
+    templates
+        .into_iter()
+        .map(|(template, (count, example))| format!("{template} {count} {example}\n"))
+        .collect()
+}
+
+fn template_segment(segment: &str) -> String {
+    // keep the extension of ids like 1234.json
+    let (stem, extension) = match segment.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+        _ => (segment, None),
+    };
+    let placeholder = if !stem.is_empty() && stem.chars().all(|c| c.is_ascii_digit()) {
+        "{int}"
+    } else if is_uuid(stem) {
+        "{uuid}"
+    } else if is_hash(stem) {
+        "{hash}"
+    } else {
+        return segment.to_string();
+    };
+    match extension {
+        Some(extension) => format!("{placeholder}.{extension}"),
+        None => placeholder.to_string(),
+    }
+}
+
+fn is_uuid(segment: &str) -> bool {
+    let groups: Vec<&str> = segment.split('-').collect();
+    groups.len() == 5
+        && groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12])
+        && groups.iter().all(|group| group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+// md5/sha1/sha256 style hex strings, or any long hex string mixing digits and letters.
+fn is_hash(segment: &str) -> bool {
+    segment.len() >= 16
+        && segment.chars().all(|c| c.is_ascii_hexdigit())
+        && segment.chars().any(|c| c.is_ascii_digit())
+        && segment.chars().any(|c| c.is_ascii_alphabetic())
+}
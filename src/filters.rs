@@ -0,0 +1,45 @@
+use regex::Regex;
+use reqwest::Url;
+
+// --include-regex/--exclude-regex value: [url|host|path|query:]regex
+#[derive(Clone)]
+pub struct RegexFilter {
+    target: String,
+    regex: Regex,
+}
+
+impl RegexFilter {
+    pub fn parse(arg: &str) -> Result<RegexFilter, String> {
+        let (target, pattern) = match arg.split_once(':') {
+            Some((target, pattern)) if ["url", "host", "path", "query"].contains(&target) => (target, pattern),
+            _ => ("url", arg),
+        };
+        let regex = Regex::new(pattern).map_err(|e| format!("invalid regex '{pattern}': {e}"))?;
+        Ok(RegexFilter {
+            target: target.to_string(),
+            regex,
+        })
+    }
+
+    pub fn is_match(&self, url: &str) -> bool {
+        if self.target == "url" {
+            return self.regex.is_match(url);
+        }
+        let parsed = match Url::parse(url) {
+            Ok(parsed) => parsed,
+            Err(_) => return false,
+        };
+        let part = match self.target.as_str() {
+            "host" => parsed.host_str().unwrap_or(""),
+            "path" => parsed.path(),
+            _ => parsed.query().unwrap_or(""),
+        };
+        self.regex.is_match(part)
+    }
+}
+
+// Kept if it matches one of the includes (when there are any) and none of the excludes.
+pub fn keep_url(url: &str, include: &[RegexFilter], exclude: &[RegexFilter]) -> bool {
+    (include.is_empty() || include.iter().any(|filter| filter.is_match(url)))
+        && !exclude.iter().any(|filter| filter.is_match(url))
+}
@@ -1,19 +1,41 @@
 extern crate clap;
+mod audit;
+mod classify;
+mod compare;
+mod diff;
+mod endpoints;
+mod filters;
+mod mirror;
+mod normalize;
+mod open_redirect;
+mod params;
+mod probe;
+mod reduce;
+mod reflect;
+mod robots;
+mod scope;
+mod sitemaps;
+mod snapshot;
+mod store;
+mod subdomains;
+mod wellknown;
+mod wordlist;
+
 use ansi_term::Colour;
 use clap::{Arg, Command};
-use reqwest::header::{HeaderValue, LOCATION};
+use reqwest::header::{HeaderValue, CONTENT_TYPE, LOCATION, ORIGIN};
 use reqwest::{redirect, Response, Url};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::{Arc, Mutex};
 use std::{io, time};
 use tokio::time::sleep;
-use futures::{stream, StreamExt, TryStreamExt};
-use tokio_util::codec::{FramedRead, LinesCodec};
-use tokio_util::io::StreamReader;
+use futures::{stream, StreamExt};
 
 #[tokio::main]
 async fn main() {
@@ -96,6 +118,34 @@ async fn main() {
                     .long("whitelist")
                     .value_name("extensions to whitelist")
                     .help("The extensions you want to whitelist (ie: -w png,jpg,txt)")
+            ).arg(
+                Arg::new("mime_include")
+                    .long("mime-include")
+                    .value_name("mimetypes")
+                    .help("Only get the urls archived with these mimetypes (ie: --mime-include application/json,text/javascript)")
+            ).arg(
+                Arg::new("mime_exclude")
+                    .long("mime-exclude")
+                    .value_name("mimetypes")
+                    .help("Don't get the urls archived with these mimetypes (ie: --mime-exclude image/*,text/css)")
+            ).arg(
+                Arg::new("scope")
+                    .long("scope")
+                    .value_name("FILE")
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .help("Scope file (hosts, *.wildcards, IPs/CIDRs, ! or - for out-of-scope entries), out-of-scope urls are dropped before any check")
+            ).arg(
+                Arg::new("include_regex")
+                    .long("include-regex")
+                    .value_name("[url|host|path|query:]regex")
+                    .action(clap::ArgAction::Append)
+                    .help("Only keep the urls matching one of these regexes (ie: --include-regex path:^/api/), can be repeated")
+            ).arg(
+                Arg::new("exclude_regex")
+                    .long("exclude-regex")
+                    .value_name("[url|host|path|query:]regex")
+                    .action(clap::ArgAction::Append)
+                    .help("Drop the urls matching one of these regexes (ie: --exclude-regex path:/wp-content/), can be repeated")
             ).arg(
                 Arg::new("blacklist code")
                     .short('z')
@@ -108,11 +158,66 @@ async fn main() {
                     .long("whitelist-code")
                     .value_name("codes to whitelist")
                     .help("The status codes you want to blacklist (ie: --whitelist-code 404,403,500)")
+            ).arg(
+                Arg::new("store_responses")
+                    .long("store-responses")
+                    .value_name("DIR")
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .help("Save the status line, headers and body of checked urls under DIR/host/path (with an index.txt)")
+            ).arg(
+                Arg::new("reflect")
+                    .long("reflect")
+                    .help("Replace the parameter values of archived urls with canaries and report the reflected ones")
+                    .action(clap::ArgAction::SetTrue),
+            ).arg(
+                Arg::new("open_redirect")
+                    .long("open-redirect")
+                    .help("Put an external canary url in redirect-like parameters and report the redirections to it")
+                    .action(clap::ArgAction::SetTrue),
+            ).arg(
+                Arg::new("audit_headers")
+                    .long("audit-headers")
+                    .help("Audit security, CORS and CDN/cache headers of checked urls and print a summary per host")
+                    .action(clap::ArgAction::SetTrue),
+            ).arg(
+                Arg::new("normalize")
+                    .long("normalize")
+                    .value_name("rules")
+                    .num_args(0..=1)
+                    .default_missing_value("all")
+                    .help("Canonicalize and dedup urls across all domains before filtering (rules: host,port,slash,encoding,fragment,query, default: all)")
+            ).arg(
+                Arg::new("reduce")
+                    .long("reduce")
+                    .help("Keep one url per host, path and set of parameter names, and drop static assets and content-like paths")
+                    .action(clap::ArgAction::SetTrue),
+            ).arg(
+                Arg::new("endpoints")
+                    .long("endpoints")
+                    .help("Print endpoint templates ({int}, {uuid}, {hash} placeholders) with their count and an example instead of the urls")
+                    .action(clap::ArgAction::SetTrue),
+            ).arg(
+                Arg::new("classify")
+                    .long("classify")
+                    .help("Tag urls with the pattern sets they match (xss, ssrf, redirect, lfi, sqli, idor, rce)")
+                    .action(clap::ArgAction::SetTrue),
+            ).arg(
+                Arg::new("patterns")
+                    .long("patterns")
+                    .value_name("FILE")
+                    .value_parser(clap::value_parser!(PathBuf))
+                    .action(clap::ArgAction::Append)
+                    .help("JSON or TOML file with more patterns (tag -> regexes, or a gf pattern file), can be repeated")
+            ).arg(
+                Arg::new("tag")
+                    .long("tag")
+                    .value_name("tags")
+                    .help("Only keep the urls matching one of these tags (ie: --tag xss,ssrf)")
             )
         )
         .subcommand(
             Command::new("robots")
-                .about("Get all disallowed entries from robots.txt")
+                .about("Get all entries from robots.txt")
                 .arg(Arg::new("domain")
                     .value_name("domain.com or file.txt or stdin")
                     .help("domain name or file with domains")
@@ -121,7 +226,87 @@ async fn main() {
                     Arg::new("output_filepath")
                         .short('o').long("output-file").value_name("FILE")
                         .value_parser(clap::value_parser!(PathBuf))
-                        .help("Name of the file to write the list of uniq paths (default: print on stdout)"))
+                        .help("Name of the file to write the list of uniq entries (default: print on stdout)"))
+                .args(archive_args())
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print the entries as JSON (directive, value and user-agents)")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("paths_only")
+                        .long("paths-only")
+                        .help("Only print the uniq allow/disallow paths")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("history")
+                        .long("history")
+                        .help("Print when each entry was first and last seen, and if it is still in the latest robots.txt")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("expand")
+                        .long("expand")
+                        .help("List the archived urls matching each allow/disallow pattern")
+                        .conflicts_with("json")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("check")
+                        .short('c')
+                        .long("check")
+                        .help("Check the HTTP status of the urls listed by --expand")
+                        .requires("expand")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("verbose")
+                        .long("verbose")
+                        .short('v')
+                        .help("Print all informations")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("sitemaps")
+                .about("Get all urls listed in the archived sitemaps of a domain")
+                .arg(Arg::new("domain")
+                    .value_name("domain.com or file.txt or stdin")
+                    .help("domain name or file with domains")
+                    .required(true))
+                .arg(
+                    Arg::new("output_filepath")
+                        .short('o')
+                        .long("output-file")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Name of the file to write the urls and their lastmod (default: print on stdout)"))
+                .args(archive_args())
+                .arg(
+                    Arg::new("verbose")
+                        .long("verbose")
+                        .short('v')
+                        .help("Print all informations")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("wellknown")
+                .about("Get the hosts, paths and endpoints of the archived .well-known and metadata files of a domain")
+                .arg(Arg::new("domain")
+                    .value_name("domain.com or file.txt or stdin")
+                    .help("domain name or file with domains")
+                    .required(true))
+                .arg(
+                    Arg::new("output_filepath")
+                        .short('o')
+                        .long("output-file")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Name of the file to write the extracted entries (default: print on stdout)"))
+                .args(archive_args())
                 .arg(
                     Arg::new("verbose")
                         .long("verbose")
@@ -144,6 +329,184 @@ async fn main() {
                         .value_name("FILE")
                         .value_parser(clap::value_parser!(PathBuf))
                         .help("Name of the file to write contents of archives (default: print on stdout)"))
+                .arg(
+                    Arg::new("download_dir")
+                        .long("download-dir")
+                        .value_name("DIR")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .conflicts_with("output_filepath")
+                        .help("Save each capture to DIR/host/path/<timestamp> with a <timestamp>.json metadata file instead of concatenating them (already saved captures are skipped)"))
+                .args(archive_args())
+                .arg(
+                    Arg::new("verbose")
+                        .long("verbose")
+                        .short('v')
+                        .help("Print all informations")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Get the unified diffs between the archived versions of a url")
+                .arg(Arg::new("url")
+                    .value_name("url or file")
+                    .help("url or file with urls")
+                    .required(true))
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("timestamp")
+                        .help("Diff the version archived at or before this timestamp (ie: 2019 or 20190601) against --to (default: first archive)"))
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_name("timestamp")
+                        .help("Diff --from against the version archived at or before this timestamp (ie: 2023 or 20231231) (default: latest archive)"))
+                .arg(
+                    Arg::new("ignore_whitespace")
+                        .short('w')
+                        .long("ignore-whitespace")
+                        .help("Ignore changes in indentation, spacing and blank lines")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("output_filepath")
+                        .short('o')
+                        .long("output-file")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Name of the file to write the diffs (default: print on stdout)"))
+                .args(archive_args())
+                .arg(
+                    Arg::new("verbose")
+                        .long("verbose")
+                        .short('v')
+                        .help("Print all informations")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("wordlist")
+                .about("Build wordlists from the archived urls of a domain")
+                .arg(Arg::new("domain")
+                    .value_name("domain.com or file.txt or stdin")
+                    .help("domain name or file with domains")
+                    .required(true))
+                .arg(
+                    Arg::new("subs")
+                        .short('s')
+                        .long("subs")
+                        .help("Get subdomains too")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("ranked")
+                        .short('r')
+                        .long("ranked")
+                        .help("Sort words by frequency instead of alphabetically")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("output_dir")
+                        .short('o')
+                        .long("output-dir")
+                        .value_name("DIR")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Directory to write directories.txt, filenames.txt, extensions.txt, parameters.txt and values.txt (default: print on stdout)"))
+                .arg(
+                    Arg::new("verbose")
+                        .long("verbose")
+                        .short('v')
+                        .help("Print all informations")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("subdomains")
+                .about("Get the subdomains of a domain seen in the archive")
+                .arg(Arg::new("domain")
+                    .value_name("domain.com or file.txt or stdin")
+                    .help("domain name or file with domains")
+                    .required(true))
+                .arg(
+                    Arg::new("check")
+                        .short('c')
+                        .long("check")
+                        .help("Check if each host still resolves and answers over http and https")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("hosts_only")
+                        .long("hosts-only")
+                        .help("Only print the hosts (to use them as input of the other subcommands)")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("threads")
+                        .short('t')
+                        .long("threads")
+                        .help("Number of concurrent requests for --check (default: 24)")
+                        .value_name("Number of concurrent requests")
+                        .value_parser(clap::value_parser!(usize))
+                )
+                .arg(
+                    Arg::new("output_filepath")
+                        .short('o')
+                        .long("output-file")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Name of the file to write the subdomains (default: print on stdout)"))
+                .arg(
+                    Arg::new("verbose")
+                        .long("verbose")
+                        .short('v')
+                        .help("Print all informations")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("params")
+                .about("Get every query parameter of the archived urls of a domain (JSON)")
+                .arg(Arg::new("domain")
+                    .value_name("domain.com or file.txt or stdin")
+                    .help("domain name or file with domains")
+                    .required(true))
+                .arg(
+                    Arg::new("subs")
+                        .short('s')
+                        .long("subs")
+                        .help("Get subdomains too")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("output_filepath")
+                        .short('o')
+                        .long("output-file")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Name of the file to write the JSON inventory (default: print on stdout)"))
+                .arg(
+                    Arg::new("verbose")
+                        .long("verbose")
+                        .short('v')
+                        .help("Print all informations")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("compare")
+                .about("Compare the live content of urls with their latest archive")
+                .arg(Arg::new("url")
+                    .value_name("url or file")
+                    .help("url or file with urls")
+                    .required(true))
+                .arg(
+                    Arg::new("output_filepath")
+                        .short('o')
+                        .long("output-file")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Name of the file to write the comparison results (default: print on stdout)"))
                 .arg(
                     Arg::new("verbose")
                         .long("verbose")
@@ -216,6 +579,69 @@ async fn main() {
                 Colour::RGB(255, 165, 0).bold().paint("Warning:")
             );
         }
+        let store_responses = argsmatches.get_one::<PathBuf>("store_responses").cloned();
+        let reflect = argsmatches.get_flag("reflect");
+        let open_redirect = argsmatches.get_flag("open_redirect");
+        let reduce = argsmatches.get_flag("reduce");
+        let endpoints = argsmatches.get_flag("endpoints");
+        let header_audit = if argsmatches.get_flag("audit_headers") {
+            if !check {
+                println!(
+                    "{} --audit-headers is useless when --nocheck is used.",
+                    Colour::RGB(255, 165, 0).bold().paint("Warning:")
+                );
+            }
+            Some(Arc::new(Mutex::new(audit::HeaderAudit::default())))
+        } else {
+            None
+        };
+        let normalizer = argsmatches.get_one::<String>("normalize").map(|rules| {
+            match normalize::Normalizer::new(rules) {
+                Ok(normalizer) => Arc::new(normalizer),
+                Err(e) => {
+                    eprintln!("{} {e}", Colour::Red.bold().paint("Error:"));
+                    process::exit(1)
+                }
+            }
+        });
+        let scope = argsmatches.get_one::<PathBuf>("scope").map(|path| {
+            match scope::Scope::from_file(path) {
+                Ok(scope) => Arc::new(scope),
+                Err(e) => {
+                    eprintln!("{} {e}", Colour::Red.bold().paint("Error:"));
+                    process::exit(1)
+                }
+            }
+        });
+        let mut cdx_filters = Vec::new();
+        if let Some(arg) = argsmatches.get_one::<String>("mime_include") {
+            cdx_filters.push(format!("mimetype:{}", mime_regex(arg)));
+        }
+        if let Some(arg) = argsmatches.get_one::<String>("mime_exclude") {
+            cdx_filters.push(format!("!mimetype:{}", mime_regex(arg)));
+        }
+        let include_regex = get_regex_filters(argsmatches, "include_regex");
+        let exclude_regex = get_regex_filters(argsmatches, "exclude_regex");
+        let classify = argsmatches.get_flag("classify");
+        let tags: Vec<String> = match argsmatches.get_one::<String>("tag") {
+            Some(arg) => arg.split(',').map(String::from).collect(),
+            None => Vec::new(),
+        };
+        let pattern_files: Vec<&Path> = argsmatches
+            .get_many::<PathBuf>("patterns")
+            .map(|files| files.map(PathBuf::as_path).collect())
+            .unwrap_or_default();
+        let classifier = if classify || !tags.is_empty() || !pattern_files.is_empty() {
+            match classify::Classifier::new(&pattern_files) {
+                Ok(classifier) => Some(Arc::new(classifier)),
+                Err(e) => {
+                    eprintln!("{} {e}", Colour::Red.bold().paint("Error:"));
+                    process::exit(1)
+                }
+            }
+        } else {
+            None
+        };
         let config = UrlConfig {
             subs,
             check,
@@ -227,6 +653,20 @@ async fn main() {
             workers: *workers,
             blacklist_code,
             whitelist_code,
+            store_responses,
+            reflect,
+            open_redirect,
+            header_audit,
+            normalizer,
+            reduce,
+            endpoints,
+            classifier,
+            classify,
+            tags,
+            include_regex,
+            exclude_regex,
+            scope,
+            cdx_filters,
         };
 
         run_urls(domains,config,filepath)
@@ -235,12 +675,38 @@ async fn main() {
 
     // get all disallow robots
     if let Some(argsmatches) = argsmatches.subcommand_matches("robots") {
+        let output_filepath = argsmatches.get_one::<PathBuf>("output_filepath");
+        let domain_or_file = argsmatches.get_one::<String>("domain").unwrap();
+        let domains = get_domains(domain_or_file);
+        let config = RobotsConfig {
+            archive: get_archive_config(argsmatches),
+            verbose: argsmatches.get_flag("verbose"),
+            json: argsmatches.get_flag("json"),
+            paths_only: argsmatches.get_flag("paths_only"),
+            history: argsmatches.get_flag("history"),
+            expand: argsmatches.get_flag("expand"),
+            check: argsmatches.get_flag("check"),
+        };
+
+        run_robots(domains, output_filepath, config).await;
+    }
+
+    if let Some(argsmatches) = argsmatches.subcommand_matches("sitemaps") {
+        let output_filepath = argsmatches.get_one::<PathBuf>("output_filepath");
+        let domain_or_file = argsmatches.get_one::<String>("domain").unwrap();
+        let domains = get_domains(domain_or_file);
+        let verbose = argsmatches.get_flag("verbose");
+
+        run_sitemaps(domains, output_filepath, verbose, get_archive_config(argsmatches)).await;
+    }
+
+    if let Some(argsmatches) = argsmatches.subcommand_matches("wellknown") {
         let output_filepath = argsmatches.get_one::<PathBuf>("output_filepath");
         let domain_or_file = argsmatches.get_one::<String>("domain").unwrap();
         let domains = get_domains(domain_or_file);
         let verbose = argsmatches.get_flag("verbose");
 
-        run_robots(domains, output_filepath, verbose).await;
+        run_wellknown(domains, output_filepath, verbose, get_archive_config(argsmatches)).await;
     }
 
     if let Some(argsmatches) = argsmatches.subcommand_matches("unify") {
@@ -250,34 +716,137 @@ async fn main() {
         let urls = get_domains(url_or_file);
         let verbose = argsmatches.get_flag("verbose");
 
-        run_unify(urls, output_filepath, verbose).await;
+        let archive = get_archive_config(argsmatches);
+
+        match argsmatches.get_one::<PathBuf>("download_dir") {
+            Some(dir) => run_mirror(urls, dir, verbose, archive).await,
+            None => run_unify(urls, output_filepath, verbose, archive).await,
+        }
     }
-}
 
-#[derive(Clone)]
-struct UrlConfig {
-    subs: bool,
-    check: bool,
-    delay: u64,
-    color: bool,
-    verbose: bool,
-    blacklist: Vec<String>,
-    whitelist: Vec<String>,
-    workers: usize,
-    blacklist_code: Vec<u16>,
-    whitelist_code: Vec<u16>,
-}
+    if let Some(argsmatches) = argsmatches.subcommand_matches("diff") {
+        let output_filepath = argsmatches.get_one::<PathBuf>("output_filepath");
+        let url_or_file = argsmatches.get_one::<String>("url").unwrap();
 
+        let urls = get_domains(url_or_file);
+        let config = DiffConfig {
+            archive: get_archive_config(argsmatches),
+            from: argsmatches.get_one::<String>("from").cloned(),
+            to: argsmatches.get_one::<String>("to").cloned(),
+            ignore_whitespace: argsmatches.get_flag("ignore_whitespace"),
+            verbose: argsmatches.get_flag("verbose"),
+        };
 
-fn get_domains(domain_or_file: &String) -> Vec<String> {
-    if domain_or_file.ne("stdin") {
-        if Path::new(domain_or_file).is_file() {
-            let path = Path::new(domain_or_file);
-            let display = path.display();
+        run_diff(urls, output_filepath, config).await;
+    }
 
-            // Open the path in read-only mode, returns `io::Result<File>`
-            let mut file = match File::open(path) {
-                // The `description` method of `io::Error` returns a string that
+    if let Some(argsmatches) = argsmatches.subcommand_matches("wordlist") {
+        let output_dir = argsmatches.get_one::<PathBuf>("output_dir");
+        let domain_or_file = argsmatches.get_one::<String>("domain").unwrap();
+        let domains = get_domains(domain_or_file);
+        let subs = argsmatches.get_flag("subs");
+        let ranked = argsmatches.get_flag("ranked");
+        let verbose = argsmatches.get_flag("verbose");
+
+        run_wordlist(domains, subs, ranked, output_dir, verbose).await;
+    }
+
+    if let Some(argsmatches) = argsmatches.subcommand_matches("subdomains") {
+        let output_filepath = argsmatches.get_one::<PathBuf>("output_filepath");
+        let domain_or_file = argsmatches.get_one::<String>("domain").unwrap();
+        let domains = get_domains(domain_or_file);
+        let check = argsmatches.get_flag("check");
+        let hosts_only = argsmatches.get_flag("hosts_only");
+        let workers = argsmatches.get_one::<usize>("threads").unwrap_or(&24);
+        let verbose = argsmatches.get_flag("verbose");
+
+        run_subdomains(domains, check, hosts_only, *workers, output_filepath, verbose).await;
+    }
+
+    if let Some(argsmatches) = argsmatches.subcommand_matches("params") {
+        let output_filepath = argsmatches.get_one::<PathBuf>("output_filepath");
+        let domain_or_file = argsmatches.get_one::<String>("domain").unwrap();
+        let domains = get_domains(domain_or_file);
+        let subs = argsmatches.get_flag("subs");
+        let verbose = argsmatches.get_flag("verbose");
+
+        run_params(domains, subs, output_filepath, verbose).await;
+    }
+
+    if let Some(argsmatches) = argsmatches.subcommand_matches("compare") {
+        let output_filepath = argsmatches.get_one::<PathBuf>("output_filepath");
+        let url_or_file = argsmatches.get_one::<String>("url").unwrap();
+
+        let urls = get_domains(url_or_file);
+        let verbose = argsmatches.get_flag("verbose");
+
+        run_compare(urls, output_filepath, verbose).await;
+    }
+}
+
+// "application/json,image/*" -> "(application/json|image/.*)" for a CDX mimetype filter
+fn mime_regex(mimetypes: &str) -> String {
+    let alternatives: Vec<String> = mimetypes
+        .split(',')
+        .map(|mimetype| regex::escape(mimetype.trim()).replace("\\*", ".*"))
+        .collect();
+    format!("({})", alternatives.join("|"))
+}
+
+fn get_regex_filters(argsmatches: &clap::ArgMatches, id: &str) -> Vec<filters::RegexFilter> {
+    argsmatches
+        .get_many::<String>(id)
+        .map(|args| {
+            args.map(|arg| match filters::RegexFilter::parse(arg) {
+                Ok(filter) => filter,
+                Err(e) => {
+                    eprintln!("{} {e}", Colour::Red.bold().paint("Error:"));
+                    process::exit(1)
+                }
+            })
+            .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Default)]
+struct UrlConfig {
+    subs: bool,
+    check: bool,
+    delay: u64,
+    color: bool,
+    verbose: bool,
+    blacklist: Vec<String>,
+    whitelist: Vec<String>,
+    workers: usize,
+    blacklist_code: Vec<u16>,
+    whitelist_code: Vec<u16>,
+    store_responses: Option<PathBuf>,
+    reflect: bool,
+    open_redirect: bool,
+    header_audit: Option<Arc<Mutex<audit::HeaderAudit>>>,
+    normalizer: Option<Arc<normalize::Normalizer>>,
+    reduce: bool,
+    endpoints: bool,
+    classifier: Option<Arc<classify::Classifier>>,
+    classify: bool,
+    tags: Vec<String>,
+    include_regex: Vec<filters::RegexFilter>,
+    exclude_regex: Vec<filters::RegexFilter>,
+    scope: Option<Arc<scope::Scope>>,
+    cdx_filters: Vec<String>,
+}
+
+
+fn get_domains(domain_or_file: &String) -> Vec<String> {
+    if domain_or_file.ne("stdin") {
+        if Path::new(domain_or_file).is_file() {
+            let path = Path::new(domain_or_file);
+            let display = path.display();
+
+            // Open the path in read-only mode, returns `io::Result<File>`
+            let mut file = match File::open(path) {
+                // The `description` method of `io::Error` returns a string that
                 // describes the error
                 Err(why) => panic!("couldn't open {}: {}", display, why),
                 Ok(file) => file,
@@ -320,6 +889,14 @@ async fn run_urls(domains: Vec<String>, config: UrlConfig, filepath: Option<&Pat
         let ret_url = handle.await.expect("fail");
         output_string.push_str(ret_url.as_str());
     }
+    if let Some(header_audit) = &config.header_audit {
+        let summary = header_audit.lock().unwrap().summary();
+        print!("{summary}");
+        output_string.push_str(summary.as_str());
+    }
+    if let Some(scope) = &config.scope {
+        println!("{dropped} out-of-scope urls dropped", dropped=scope.dropped());
+    }
     if let Some(filepath) = filepath {
         write_string_to_file(output_string, filepath);
         println!("urls saved to {display}", display=&filepath.display())
@@ -332,21 +909,11 @@ fn get_path(url: &str) -> String {
     }
 }
 
-async fn run_url(domain: String, config: UrlConfig) -> String {
-    let pattern = if config.subs {
-        format!("*.{domain}/*")
-    } else {
-        format!("{domain}/*")
-    };
-
-    let url = format!(
-        "http://web.archive.org/cdx/search/cdx?url={pattern}&output=text&fl=original&collapse=urlkey"
-    );
-
-    let client = reqwest::Client::new();
+// GET on the CDX api, retried 5 times before giving up.
+async fn cdx_request(client: &reqwest::Client, url: &str) -> Response {
     let mut response = None;
     for attempt in 1..=5 {
-        match client.get(url.as_str()).send().await {
+        match client.get(url).send().await {
             Ok(res) => {
                 response = Some(res);
                 break;
@@ -363,7 +930,26 @@ async fn run_url(domain: String, config: UrlConfig) -> String {
             }
         }
     }
-    let response = response.expect("Failed to get a response after 5 attempts");
+    response.expect("Failed to get a response after 5 attempts")
+}
+
+// Query the CDX api for every archived url of the domain (echo prints them as they arrive).
+// cdx_filters are passed as CDX filter parameters (ie: "!mimetype:image/.*").
+async fn get_archived_urls(domain: &str, subs: bool, cdx_filters: &[String], echo: bool) -> Vec<String> {
+    let pattern = if subs {
+        format!("*.{domain}/*")
+    } else {
+        format!("{domain}/*")
+    };
+
+    let mut url = Url::parse(&format!(
+        "http://web.archive.org/cdx/search/cdx?url={pattern}&output=text&fl=original&collapse=urlkey"
+    )).unwrap();
+    for filter in cdx_filters {
+        url.query_pairs_mut().append_pair("filter", filter);
+    }
+
+    let response = cdx_request(&reqwest::Client::new(), url.as_str()).await;
     use tokio_util::io::StreamReader;
     use tokio_util::codec::{FramedRead, LinesCodec};
     use futures::{StreamExt, TryStreamExt};
@@ -377,13 +963,27 @@ async fn run_url(domain: String, config: UrlConfig) -> String {
     let mut urls = Vec::new();
     while let Some(line_result) = lines.next().await {
         if let Ok(line) = line_result {
-            urls.push(line.to_string());
-            println!("{line}");
+            if echo {
+                println!("{line}");
+            }
+            urls.push(line);
         }
     }
+    urls
+}
+
+async fn run_url(domain: String, config: UrlConfig) -> String {
+    let mut urls = get_archived_urls(&domain, config.subs, &config.cdx_filters, true).await;
+
+    if let Some(normalizer) = &config.normalizer {
+        urls = normalizer.normalize_urls(urls);
+    }
+    if let Some(scope) = &config.scope {
+        urls = scope.filter_urls(urls);
+    }
 
     // Applique blacklist/whitelist
-    let filtered_urls: Vec<String> = if !config.whitelist.is_empty() {
+    let mut filtered_urls: Vec<String> = if !config.whitelist.is_empty() {
         urls.into_iter()
             .filter(|url| config.whitelist.iter().any(|ext| get_path(url).ends_with(ext)))
             .collect()
@@ -392,40 +992,148 @@ async fn run_url(domain: String, config: UrlConfig) -> String {
             .filter(|url| !config.blacklist.iter().any(|ext| get_path(url).ends_with(ext)))
             .collect()
     };
+    if !config.include_regex.is_empty() || !config.exclude_regex.is_empty() {
+        filtered_urls.retain(|url| filters::keep_url(url, &config.include_regex, &config.exclude_regex));
+    }
+    if config.reduce {
+        filtered_urls = reduce::reduce_urls(filtered_urls);
+    }
+    if let Some(classifier) = &config.classifier {
+        if !config.tags.is_empty() {
+            filtered_urls.retain(|url| classifier.tags(url).iter().any(|tag| config.tags.iter().any(|t| t == tag)));
+        }
+    }
+
+    // the active checks below only send requests to urls of the queried domain
+    let active_urls: Vec<String> = if config.reflect || config.open_redirect {
+        filtered_urls
+            .iter()
+            .filter(|url| url_in_domain(url, &domain, config.subs))
+            .cloned()
+            .collect()
+    } else {
+        Vec::new()
+    };
 
-    if config.check {
+    let mut output = if config.endpoints {
+        let templates = endpoints::endpoint_templates(&filtered_urls);
+        print!("{templates}");
+        templates
+    } else if config.check {
         if config.delay > 0 {
-            http_status_urls_delay(
-                filtered_urls,
-                config.delay,
-                config.color,
-                config.verbose,
-                &config.blacklist_code,
-                &config.whitelist_code,
-            )
-            .await
+            http_status_urls_delay(filtered_urls, &config).await
         } else {
-            http_status_urls_no_delay(
-                filtered_urls,
-                config.color,
-                config.verbose,
-                config.workers,
-                &config.blacklist_code,
-                &config.whitelist_code,
-            )
-            .await
+            http_status_urls_no_delay(filtered_urls, &config).await
         }
     } else {
-        println!("{}", filtered_urls.join("\n"));
-        filtered_urls.join("\n")
+        let lines: Vec<String> = if config.classify {
+            filtered_urls.iter().map(|url| format!("{url}{tags}", tags=url_tags(url, &config))).collect()
+        } else {
+            filtered_urls
+        };
+        println!("{}", lines.join("\n"));
+        lines.join("\n")
+    };
+
+    if config.reflect {
+        let client = no_redirect_client();
+        output.push_str(
+            reflect::check_reflections(&active_urls, &client, config.workers, config.delay, config.verbose)
+                .await
+                .as_str(),
+        );
+    }
+    if config.open_redirect {
+        let client = no_redirect_client();
+        output.push_str(
+            open_redirect::check_open_redirects(&active_urls, &client, config.workers, config.delay, config.verbose)
+                .await
+                .as_str(),
+        );
     }
+    output
 }
 
+// " [xss,ssrf]" when --classify is set and the url matches some tags.
+fn url_tags(url: &str, config: &UrlConfig) -> String {
+    match (&config.classifier, config.classify) {
+        (Some(classifier), true) => {
+            let tags = classifier.tags(url);
+            if tags.is_empty() {
+                String::new()
+            } else {
+                format!(" [{tags}]", tags=tags.join(","))
+            }
+        }
+        _ => String::new(),
+    }
+}
 
-async fn run_robots(domains: Vec<String>, output_filepath: Option<&PathBuf>, verbose: bool) {
+// True if the host of url is the domain (or one of its subdomains when subs is set).
+fn url_in_domain(url: &str, domain: &str, subs: bool) -> bool {
+    let domain = domain.trim_end_matches('/').to_lowercase();
+    match Url::parse(url) {
+        Ok(parsed) => match parsed.host_str() {
+            Some(host) => {
+                let host = host.to_lowercase();
+                host == domain
+                    || host == format!("www.{domain}")
+                    || (subs && host.ends_with(format!(".{domain}").as_str()))
+            }
+            None => false,
+        },
+        Err(_) => false,
+    }
+}
+
+
+async fn run_robots(domains: Vec<String>, output_filepath: Option<&PathBuf>, config: RobotsConfig) {
     let mut output_string = String::new();
+    let mut json_output = serde_json::Map::new();
     for domain in domains {
-        output_string.push_str(run_robot(domain, verbose).await.as_str());
+        let (entries, latest) = run_robot(&domain, &config).await;
+        if config.expand {
+            let expanded = expand_robots_entries(&domain, &entries, &config).await;
+            output_string.push_str(expanded.as_str());
+            continue;
+        }
+        if config.json {
+            let entries: Vec<serde_json::Value> = entries
+                .iter()
+                .map(|seen| {
+                    let mut value = seen.entry.to_json();
+                    if config.history {
+                        value["first_seen"] = seen.first_seen.clone().into();
+                        value["last_seen"] = seen.last_seen.clone().into();
+                        value["present"] = (Some(&seen.last_seen) == latest.as_ref()).into();
+                    }
+                    value
+                })
+                .collect();
+            json_output.insert(domain, serde_json::Value::Array(entries));
+        } else if config.history {
+            for seen in entries {
+                let state = if Some(&seen.last_seen) == latest.as_ref() { "present" } else { "removed" };
+                let line = format!(
+                    "{line} first={first} last={last} {state}\n",
+                    line=robots_line(&seen.entry, &config),
+                    first=seen.first_seen,
+                    last=seen.last_seen
+                );
+                print!("{line}");
+                output_string.push_str(line.as_str());
+            }
+        } else {
+            for seen in entries {
+                output_string.push_str(format!("{}\n", robots_line(&seen.entry, &config)).as_str());
+            }
+        }
+    }
+    if config.json {
+        output_string = serde_json::to_string_pretty(&json_output).expect("Error serializing the entries");
+        if output_filepath.is_none() {
+            println!("{output_string}");
+        }
     }
     if let Some(filepath) = output_filepath {
         write_string_to_file(output_string, filepath);
@@ -433,21 +1141,281 @@ async fn run_robots(domains: Vec<String>, output_filepath: Option<&PathBuf>, ver
     }
 }
 
-async fn run_robot(domain: String, verbose: bool) -> String {
+// How archives are fetched from the wayback machine.
+#[derive(Clone)]
+struct ArchiveConfig {
+    client: reqwest::Client,
+    workers: usize,
+    delay: u64,
+    // wayback rendered view instead of the original bytes (id_)
+    rendered: bool,
+    // in bytes
+    max_size: u64,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        ArchiveConfig {
+            client: reqwest::Client::new(),
+            workers: 10,
+            delay: 0,
+            rendered: false,
+            max_size: 10 * 1024 * 1024,
+        }
+    }
+}
+
+// Options of the subcommands fetching archive contents, read by get_archive_config.
+fn archive_args() -> [Arg; 4] {
+    [
+        Arg::new("threads")
+            .short('t')
+            .long("threads")
+            .help("The number of archives fetched at the same time (default: 10)")
+            .value_name("numbers of threads")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("delay")
+            .short('d')
+            .long("delay")
+            .help("Make a delay between each archive request")
+            .value_name("delay in milliseconds")
+            .value_parser(clap::value_parser!(u64)),
+        Arg::new("max_size")
+            .long("max-size")
+            .help("Maximum size of an archive, bigger ones are truncated (default: 10)")
+            .value_name("size in MB")
+            .value_parser(clap::value_parser!(u64)),
+        Arg::new("rendered")
+            .long("rendered")
+            .help("Get the archives as rendered by the wayback machine (toolbar and rewritten links) instead of the original content")
+            .action(clap::ArgAction::SetTrue),
+    ]
+}
+
+fn get_archive_config(argsmatches: &clap::ArgMatches) -> ArchiveConfig {
+    ArchiveConfig {
+        workers: (*argsmatches.get_one::<usize>("threads").unwrap_or(&10)).max(1),
+        delay: *argsmatches.get_one::<u64>("delay").unwrap_or(&0),
+        rendered: argsmatches.get_flag("rendered"),
+        max_size: argsmatches.get_one::<u64>("max_size").unwrap_or(&10).saturating_mul(1024 * 1024),
+        ..Default::default()
+    }
+}
+
+#[derive(Clone)]
+struct RobotsConfig {
+    archive: ArchiveConfig,
+    verbose: bool,
+    json: bool,
+    paths_only: bool,
+    history: bool,
+    expand: bool,
+    check: bool,
+}
+
+// List the archived urls of the domain matching each allow/disallow pattern,
+// and check their status when --check is set.
+async fn expand_robots_entries(domain: &str, entries: &[robots::SeenEntry], config: &RobotsConfig) -> String {
+    let mut cdx_cache: HashMap<String, Vec<String>> = HashMap::new();
+    let mut patterns: Vec<&robots::Entry> = Vec::new();
+    for seen in entries {
+        if seen.entry.is_path_rule() && !patterns.iter().any(|entry| entry.value == seen.entry.value) {
+            patterns.push(&seen.entry);
+        }
+    }
+    let url_config = UrlConfig {
+        check: true,
+        delay: config.archive.delay,
+        color: true,
+        verbose: config.verbose,
+        workers: 24,
+        ..Default::default()
+    };
+
+    let mut ret = String::new();
+    for entry in patterns {
+        let prefix = robots::pattern_prefix(&entry.value);
+        if !cdx_cache.contains_key(&prefix) {
+            // the prefix comes from the robots.txt, it may contain & + or %
+            let mut url = Url::parse("http://web.archive.org/cdx/search/cdx?matchType=prefix&output=text&fl=original&collapse=urlkey").unwrap();
+            url.query_pairs_mut().append_pair("url", format!("{domain}{prefix}").as_str());
+            let text = cdx_request(&config.archive.client, url.as_str()).await.text().await.unwrap_or_default();
+            cdx_cache.insert(prefix.clone(), text.lines().map(String::from).collect());
+        }
+        let matching: Vec<String> = cdx_cache[&prefix]
+            .iter()
+            .filter(|url| match Url::parse(url) {
+                Ok(parsed) => {
+                    let path = match parsed.query() {
+                        Some(query) => format!("{}?{query}", parsed.path()),
+                        None => parsed.path().to_string(),
+                    };
+                    robots::path_matches(&entry.value, &path)
+                }
+                Err(_) => false,
+            })
+            .cloned()
+            .collect();
+
+        let header = format!("{} ({len} archived urls)\n", robots_line(entry, config), len=matching.len());
+        print!("{header}");
+        ret.push_str(header.as_str());
+        if config.check && url_config.delay > 0 {
+            ret.push_str(http_status_urls_delay(matching, &url_config).await.as_str());
+        } else if config.check {
+            ret.push_str(http_status_urls_no_delay(matching, &url_config).await.as_str());
+        } else {
+            for url in matching {
+                println!("{url}");
+                ret.push_str(format!("{url}\n").as_str());
+            }
+        }
+    }
+    ret
+}
+
+fn robots_line(entry: &robots::Entry, config: &RobotsConfig) -> String {
+    if config.paths_only {
+        entry.value.clone()
+    } else {
+        entry.to_text()
+    }
+}
+
+// Entries of all the robots.txt archives of the domain, and the timestamp of the latest archive.
+async fn run_robot(domain: &str, config: &RobotsConfig) -> (Vec<robots::SeenEntry>, Option<String>) {
     let url = format!("{domain}/robots.txt");
-    let archives = get_archives(url.as_str(), verbose).await;
-    get_all_robot_content(archives, verbose).await
+    let runs = if config.history {
+        get_archive_runs(&config.archive, url.as_str(), config.verbose).await
+    } else {
+        get_archives(&config.archive, url.as_str(), config.verbose)
+            .await
+            .into_iter()
+            .map(|(timestamp, url)| (timestamp.clone(), (url, timestamp)))
+            .collect()
+    };
+    let latest = runs.values().map(|(_, last)| last.clone()).max();
+    (get_all_robot_content(runs, config).await, latest)
 }
 
-async fn run_unify(urls: Vec<String>, output_filepath: Option<&PathBuf>, verbose: bool) {
+async fn run_sitemaps(domains: Vec<String>, output_filepath: Option<&PathBuf>, verbose: bool, archive: ArchiveConfig) {
     let mut output_string = String::new();
-    for url in urls {
-        let archives = get_archives(url.as_str(), verbose).await;
-        let unify_output = get_all_archives_content(archives, verbose).await;
-        output_string.push_str(unify_output.as_str());
+    for domain in domains {
+        // loc -> latest lastmod
+        let mut locs: BTreeMap<String, Option<String>> = BTreeMap::new();
+        let mut queue = find_sitemaps(&archive, &domain, verbose).await;
+        let mut visited: Vec<String> = Vec::new();
+
+        while let Some(sitemap_url) = queue.pop() {
+            if visited.contains(&sitemap_url) {
+                continue;
+            }
+            visited.push(sitemap_url.clone());
+            // every snapshot, an old sitemap index may point to sitemaps that disappeared since
+            let archives = get_archives(&archive, sitemap_url.as_str(), verbose).await;
+            for (_, content) in get_archives_contents(&archive, archives).await {
+                match sitemaps::parse(&content.into_bytes(), archive.max_size) {
+                    sitemaps::Sitemap::Index(children) => {
+                        queue.extend(children.into_iter().filter(|child| !visited.contains(child)));
+                    }
+                    sitemaps::Sitemap::UrlSet(urls) => {
+                        for (loc, lastmod) in urls {
+                            let known = locs.entry(loc).or_insert(None);
+                            if lastmod > *known {
+                                *known = lastmod;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for (loc, lastmod) in locs {
+            let line = format!("{loc} {lastmod}\n", lastmod=lastmod.unwrap_or_default());
+            print!("{line}");
+            output_string.push_str(line.as_str());
+        }
     }
     if let Some(filepath) = output_filepath {
         write_string_to_file(output_string, filepath);
+        println!("urls saved to {display}", display=filepath.display())
+    }
+}
+
+// Archived sitemap urls of the domain: sitemap*.xml(.gz) files seen by the CDX
+// api and the Sitemap: lines of the archived robots.txt.
+async fn find_sitemaps(archive: &ArchiveConfig, domain: &str, verbose: bool) -> Vec<String> {
+    let mut sitemap_urls = vec![format!("{domain}/sitemap.xml"), format!("{domain}/sitemap_index.xml")];
+
+    let mut cdx_url = Url::parse(&format!(
+        "http://web.archive.org/cdx/search/cdx?url={domain}/&matchType=prefix&output=text&fl=original&collapse=urlkey"
+    )).unwrap();
+    cdx_url.query_pairs_mut().append_pair("filter", "original:(?i).*sitemap.*\\.(xml|xml\\.gz|gz)(\\?.*)?");
+    let text = cdx_request(&archive.client, cdx_url.as_str()).await.text().await.unwrap_or_default();
+    sitemap_urls.extend(text.lines().map(String::from));
+
+    let robots_url = format!("{domain}/robots.txt");
+    let archives = get_archives(archive, robots_url.as_str(), verbose).await;
+    for (_, content) in get_archives_contents(archive, archives).await {
+        for entry in robots::parse(&content.text()) {
+            if entry.directive == "sitemap" {
+                sitemap_urls.push(entry.value);
+            }
+        }
+    }
+
+    let mut unique: Vec<String> = Vec::new();
+    for url in sitemap_urls {
+        if !unique.contains(&url) {
+            unique.push(url);
+        }
+    }
+    if verbose {
+        println!("{len} sitemaps to look for", len=unique.len());
+    }
+    unique
+}
+
+async fn run_wellknown(domains: Vec<String>, output_filepath: Option<&PathBuf>, verbose: bool, archive: ArchiveConfig) {
+    let mut output_string = String::new();
+    for domain in domains {
+        for file in wellknown::FILES {
+            let url = format!("{domain}{file}");
+            let archives = get_archives(&archive, url.as_str(), verbose).await;
+            if archives.is_empty() {
+                continue;
+            }
+            // (kind, value) -> timestamp of the first snapshot it was seen in
+            let mut found: Vec<((String, String), String)> = Vec::new();
+            for (timestamp, content) in get_archives_contents(&archive, archives).await {
+                for item in wellknown::extract(file, &content.text()) {
+                    if !found.iter().any(|(known, _)| *known == item) {
+                        found.push((item, timestamp.clone()));
+                    }
+                }
+            }
+            for ((kind, value), timestamp) in found {
+                let line = format!("{url} {kind}: {value} (first seen {timestamp})\n");
+                print!("{line}");
+                output_string.push_str(line.as_str());
+            }
+        }
+    }
+    if let Some(filepath) = output_filepath {
+        write_string_to_file(output_string, filepath);
+        println!("entries saved to {display}", display=filepath.display())
+    }
+}
+
+async fn run_unify(urls: Vec<String>, output_filepath: Option<&PathBuf>, verbose: bool, archive: ArchiveConfig) {
+    let mut output = Vec::new();
+    for url in urls {
+        let archives = get_archives(&archive, url.as_str(), verbose).await;
+        let unify_output = get_all_archives_content(&archive, archives, verbose).await;
+        output.extend_from_slice(&unify_output);
+    }
+    if let Some(filepath) = output_filepath {
+        write_string_to_file(output, filepath);
         if verbose {
             println!("urls saved to {display}", display=filepath.display())
         };
@@ -455,18 +1423,233 @@ async fn run_unify(urls: Vec<String>, output_filepath: Option<&PathBuf>, verbose
 
 }
 
-fn write_string_to_file(string: String, filename: &PathBuf) {
+async fn run_mirror(urls: Vec<String>, dir: &Path, verbose: bool, archive: ArchiveConfig) {
+    // redirect captures are saved as they were archived, not followed
+    let archive = &ArchiveConfig {
+        client: no_redirect_client(),
+        ..archive
+    };
+    for url in urls {
+        let captures: Vec<mirror::Capture> = get_captures(archive, url.as_str(), verbose)
+            .await
+            .into_iter()
+            .filter(|capture| !mirror::is_saved(&capture.path(dir)))
+            .collect();
+        if verbose {
+            println!("Downloading {len} captures of {url}...", len=captures.len());
+        }
+        let saved = stream::iter(captures)
+            .then(|capture| async move {
+                if archive.delay > 0 {
+                    sleep(time::Duration::from_millis(archive.delay)).await;
+                }
+                capture
+            })
+            .map(|capture| async move {
+                let timestampurl = snapshot_url(archive, &capture.original, &capture.timestamp);
+                let body = get_archive_body(archive, &timestampurl, capture.statuscode.parse().ok()).await;
+                // missing or partial bodies are not saved, so the next run fetches them again
+                if !body.complete {
+                    return false;
+                }
+                let path = capture.path(dir);
+                mirror::save(&path, &capture, &body.bytes);
+                println!("{url} {timestamp} saved to {display}", url=capture.original, timestamp=capture.timestamp, display=path.display());
+                true
+            })
+            .buffer_unordered(archive.workers)
+            .filter(|saved| futures::future::ready(*saved))
+            .count()
+            .await;
+        println!("{saved} new captures of {url} saved to {display}", display=dir.display());
+    }
+}
+
+struct DiffConfig {
+    archive: ArchiveConfig,
+    from: Option<String>,
+    to: Option<String>,
+    ignore_whitespace: bool,
+    verbose: bool,
+}
+
+async fn run_diff(urls: Vec<String>, output_filepath: Option<&PathBuf>, config: DiffConfig) {
+    let mut output_string = String::new();
+    for url in urls {
+        // distinct digests only, in timestamp order
+        let mut archives = get_archives(&config.archive, url.as_str(), config.verbose).await;
+        if config.from.is_some() || config.to.is_some() {
+            archives = match diff::resolve_range(&archives, config.from.as_deref(), config.to.as_deref()) {
+                Ok((from, to)) => archives
+                    .into_iter()
+                    .filter(|(timestamp, _)| *timestamp == from || *timestamp == to)
+                    .collect(),
+                Err(e) => {
+                    eprintln!("{url}: {e}");
+                    continue;
+                }
+            };
+        }
+        if config.verbose {
+            println!("Getting {len} archives...", len=archives.len());
+        }
+
+        let versions: Vec<(String, String)> = get_archives_contents(&config.archive, archives)
+            .await
+            .into_iter()
+            .map(|(timestamp, content)| (timestamp, content.text()))
+            .collect();
+        for pair in versions.windows(2) {
+            let ((old_timestamp, old), (new_timestamp, new)) = (&pair[0], &pair[1]);
+            let diff = diff::unified_diff(
+                &url,
+                old_timestamp,
+                old,
+                new_timestamp,
+                new,
+                config.ignore_whitespace,
+            );
+            print!("{diff}");
+            output_string.push_str(diff.as_str());
+        }
+    }
+    if let Some(filepath) = output_filepath {
+        write_string_to_file(output_string, filepath);
+        println!("diffs saved to {display}", display=filepath.display())
+    }
+}
+
+async fn run_wordlist(domains: Vec<String>, subs: bool, ranked: bool, output_dir: Option<&PathBuf>, verbose: bool) {
+    let mut wordlists = wordlist::Wordlists::default();
+    for domain in domains {
+        let urls = get_archived_urls(&domain, subs, &[], false).await;
+        if verbose {
+            println!("{len} archived urls for {domain}", len=urls.len());
+        }
+        for url in urls {
+            wordlists.add_url(&url);
+        }
+    }
+    match output_dir {
+        Some(dir) => {
+            wordlists.write_to_dir(dir, ranked);
+            println!("wordlists saved to {display}", display=dir.display())
+        }
+        None => print!("{}", wordlists.to_text(ranked)),
+    }
+}
+
+async fn run_subdomains(
+    domains: Vec<String>,
+    check: bool,
+    hosts_only: bool,
+    workers: usize,
+    output_filepath: Option<&PathBuf>,
+    verbose: bool,
+) {
+    let client = reqwest::ClientBuilder::new()
+        .redirect(redirect::Policy::none())
+        .timeout(time::Duration::from_secs(10))
+        .build()
+        .unwrap();
+    let mut output_string = String::new();
+    for domain in domains {
+        let mut subdomains = subdomains::get_subdomains(&domain, verbose).await;
+        if check {
+            if verbose {
+                println!("Checking {len} hosts...", len=subdomains.len());
+            }
+            subdomains.check_live(&client, workers).await;
+        }
+        let text = subdomains.to_text(hosts_only);
+        print!("{text}");
+        output_string.push_str(text.as_str());
+    }
+    if let Some(filepath) = output_filepath {
+        write_string_to_file(output_string, filepath);
+        println!("subdomains saved to {display}", display=filepath.display())
+    }
+}
+
+async fn run_params(domains: Vec<String>, subs: bool, output_filepath: Option<&PathBuf>, verbose: bool) {
+    let mut inventories = serde_json::Map::new();
+    for domain in domains {
+        let urls = get_archived_urls(&domain, subs, &[], false).await;
+        if verbose {
+            println!("{len} archived urls for {domain}", len=urls.len());
+        }
+        let mut inventory = params::ParamInventory::default();
+        for url in urls {
+            inventory.add_url(&url);
+        }
+        // on stderr so stdout stays valid JSON
+        for (name, lead) in inventory.leads() {
+            eprintln!(
+                "{} {domain}: parameter {name} carries {kind}",
+                Colour::RGB(255, 165, 0).bold().paint(format!("{} lead:", lead.to_uppercase())),
+                kind=if lead == "ssrf" { "urls" } else { "paths" }
+            );
+        }
+        inventories.insert(domain, inventory.to_json());
+    }
+    let output_string = serde_json::to_string_pretty(&inventories).expect("Error serializing the inventory");
+    match output_filepath {
+        Some(filepath) => {
+            write_string_to_file(output_string, filepath);
+            println!("inventory saved to {display}", display=filepath.display())
+        }
+        None => println!("{output_string}"),
+    }
+}
+
+async fn run_compare(urls: Vec<String>, output_filepath: Option<&PathBuf>, verbose: bool) {
+    let archive = ArchiveConfig::default();
+    let mut output_string = String::new();
+    for url in urls {
+        let archives = get_archives(&archive, url.as_str(), verbose).await;
+        let line = match archives.into_iter().next_back() {
+            Some((timestamp, archived_url)) => {
+                let archived = get_archive_content(&archive, archived_url, timestamp.clone()).await;
+                match archive.client.get(url.as_str()).send().await {
+                    Ok(response) => {
+                        let final_url = response.url().to_string();
+                        let live = response.text().await.unwrap_or_default();
+                        let mut line = compare::compare_contents(&url, &timestamp, &archived, &live, verbose);
+                        if final_url.trim_end_matches('/') != url.trim_end_matches('/') {
+                            line.push_str(format!("  redirected to {final_url}\n").as_str());
+                        }
+                        line
+                    }
+                    Err(e) => format!("{url} live request failed: {e}\n"),
+                }
+            }
+            None => format!("{url} no archive found\n"),
+        };
+        print!("{line}");
+        output_string.push_str(line.as_str());
+    }
+    if let Some(filepath) = output_filepath {
+        write_string_to_file(output_string, filepath);
+        if verbose {
+            println!("results saved to {display}", display=filepath.display())
+        };
+    }
+}
+
+fn write_string_to_file(string: impl AsRef<[u8]>, filename: &PathBuf) {
     let mut file = File::create(filename).expect("Error creating the file");
-    file.write_all(string.as_bytes())
+    file.write_all(string.as_ref())
         .expect("Error writing content to the file");
 }
 
-async fn get_archives(url: &str, verbose: bool) -> HashMap<String, String> {
+// Archives of url (timestamp -> original url), sorted by timestamp.
+async fn get_archives(archive: &ArchiveConfig, url: &str, verbose: bool) -> BTreeMap<String, String> {
     if verbose {
         println!("Looking for archives for {url}...")
     };
     let to_fetch= format!("http://web.archive.org/cdx/search/cdx?url={url}&output_filepath=text&fl=timestamp,original&filter=statuscode:200&collapse=digest");
-    let lines: Vec<String> = reqwest::get(to_fetch.as_str())
+    let lines: Vec<String> = archive.client.get(to_fetch.as_str())
+        .send()
         .await
         .expect("Error in GET request")
         .text()
@@ -475,7 +1658,7 @@ async fn get_archives(url: &str, verbose: bool) -> HashMap<String, String> {
         .lines()
         .map(|x| x.to_owned())
         .collect();
-    let mut data = HashMap::new();
+    let mut data = BTreeMap::new();
     for line in lines {
         match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
             [s1, s2] => {
@@ -489,131 +1672,241 @@ async fn get_archives(url: &str, verbose: bool) -> HashMap<String, String> {
     data
 }
 
-async fn get_all_archives_content(archives: HashMap<String, String>, verbose: bool) -> String {
+// Every capture of url (redirects, errors and repeated content included) with
+// its CDX metadata, sorted by timestamp.
+async fn get_captures(archive: &ArchiveConfig, url: &str, verbose: bool) -> Vec<mirror::Capture> {
+    if verbose {
+        println!("Looking for archives for {url}...")
+    };
+    let to_fetch = format!("http://web.archive.org/cdx/search/cdx?url={url}&output=text&fl=timestamp,original,mimetype,statuscode,digest");
+    let text = archive.client.get(to_fetch.as_str())
+        .send()
+        .await
+        .expect("Error in GET request")
+        .text()
+        .await
+        .expect("Error parsing response");
+    text.lines().filter_map(mirror::Capture::parse).collect()
+}
+
+// Runs of consecutive captures of url with the same digest (timestamp of the
+// first capture -> (original url, timestamp of the last capture)). Unlike
+// collapse=digest, this keeps when each version was last captured.
+async fn get_archive_runs(archive: &ArchiveConfig, url: &str, verbose: bool) -> BTreeMap<String, (String, String)> {
+    if verbose {
+        println!("Looking for archives for {url}...")
+    };
+    let to_fetch = format!("http://web.archive.org/cdx/search/cdx?url={url}&output=text&fl=timestamp,original,digest&filter=statuscode:200");
+    let text = archive.client.get(to_fetch.as_str())
+        .send()
+        .await
+        .expect("Error in GET request")
+        .text()
+        .await
+        .expect("Error parsing response");
+    let mut captures: Vec<Vec<&str>> = text
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>())
+        .filter(|fields| fields.len() == 3)
+        .collect();
+    captures.sort_by(|a, b| a[0].cmp(b[0]));
+
+    let mut runs = BTreeMap::new();
+    let mut current: Option<(&str, &str, &str, &str)> = None; // first, original, last, digest
+    for fields in captures {
+        let (timestamp, original, digest) = (fields[0], fields[1], fields[2]);
+        current = match current {
+            Some((first, run_url, _, run_digest)) if run_digest == digest => Some((first, run_url, timestamp, digest)),
+            Some((first, run_url, last, _)) => {
+                runs.insert(first.to_string(), (run_url.to_string(), last.to_string()));
+                Some((timestamp, original, timestamp, digest))
+            }
+            None => Some((timestamp, original, timestamp, digest)),
+        };
+    }
+    if let Some((first, run_url, last, _)) = current {
+        runs.insert(first.to_string(), (run_url.to_string(), last.to_string()));
+    }
+    runs
+}
+
+async fn get_all_archives_content(archive: &ArchiveConfig, archives: BTreeMap<String, String>, verbose: bool) -> Vec<u8> {
     if verbose {
         println!("Getting {len} archives...", len=archives.len());
     };
 
-    let mut all_text = String::new();
-    for (timestamp, url) in archives {
-        let content = get_archive_content(url, timestamp).await;
+    let mut all_content = Vec::new();
+    for (_, content) in get_archives_contents(archive, archives).await {
+        let content = content.into_bytes();
         if verbose {
-            println!("{content}");
+            io::stdout().write_all(&content).expect("Error writing to stdout");
+            println!();
         }
-        all_text.push_str(content.as_str());
+        all_content.extend_from_slice(&content);
     }
 
-    all_text.clone()
+    all_content
 }
 
-async fn get_all_robot_content(archives: HashMap<String, String>, verbose: bool) -> String {
-    if verbose {
-        println!("Getting {len} archives...", len=archives.len());
-    };
+// Fetch the content of the archives with at most archive.workers requests at a
+// time, started archive.delay ms apart. Results are in timestamp order.
+async fn get_archives_contents(archive: &ArchiveConfig, archives: BTreeMap<String, String>) -> Vec<(String, snapshot::Content)> {
+    stream::iter(archives)
+        .then(|item| async move {
+            if archive.delay > 0 {
+                sleep(time::Duration::from_millis(archive.delay)).await;
+            }
+            item
+        })
+        .map(|(timestamp, url)| async move {
+            let content = get_archive_snapshot(archive, &url, &timestamp).await;
+            (timestamp, content)
+        })
+        .buffered(archive.workers)
+        .collect()
+        .await
+}
 
-    let mut output_string = String::new();
+// Unique robots.txt entries of all the archives with the timestamps of the
+// first and last snapshots they were seen in. runs maps the first timestamp of
+// each version to its url and last timestamp, one body is fetched per version
+// and versions are processed in timestamp order.
+async fn get_all_robot_content(runs: BTreeMap<String, (String, String)>, config: &RobotsConfig) -> Vec<robots::SeenEntry> {
+    if config.verbose {
+        println!("Getting {len} archives...", len=runs.len());
+    };
 
-    for (timestamp, url) in archives {
-        let archive_content = get_archive_content(url, timestamp).await;
+    let mut entries: Vec<robots::SeenEntry> = Vec::new();
+    let archives: BTreeMap<String, String> = runs
+        .iter()
+        .map(|(first, (url, _))| (first.clone(), url.clone()))
+        .collect();
 
-        let disallowed_lines: Vec<String> = archive_content
-            .lines()
-            .filter(|line| line.contains("low:"))
-            .map(|s| s.replace("Disallow:", "").replace("Allow:", ""))
-            .collect();
+    for (timestamp, archive_content) in get_archives_contents(&config.archive, archives).await {
+        let last = &runs[&timestamp].1;
 
-        for line in disallowed_lines {
-            if !output_string.contains(&line) {
-                output_string.push_str(format!("{line}\n").as_str());
-                if verbose {
-                    let trimmed = line.trim();
-                    println!("{trimmed}");
+        for entry in robots::parse(&archive_content.text()) {
+            if config.paths_only && !entry.is_path_rule() {
+                continue;
+            }
+            let found = entries.iter_mut().find(|found| {
+                found.entry == entry || (config.paths_only && found.entry.value == entry.value)
+            });
+            match found {
+                Some(found) => found.last_seen = last.clone(),
+                None => {
+                    if !config.json && !config.history {
+                        println!("{}", robots_line(&entry, config));
+                    }
+                    entries.push(robots::SeenEntry {
+                        entry,
+                        first_seen: timestamp.clone(),
+                        last_seen: last.clone(),
+                    });
                 }
             }
         }
     }
-    output_string
+    entries
 }
 
-// Unbuffered get_archive_content
-async fn get_archive_content(url: String, timestamp: String) -> String {
-    let timestampurl = format!("http://web.archive.org/web/{timestamp}/{url}");
-    let response = match reqwest::get(&timestampurl).await {
+// Body of a snapshot url, complete is false when the body is missing or
+// partial (request error, non-2xx status, read error or truncation).
+struct ArchiveBody {
+    content_type: Option<String>,
+    bytes: Vec<u8>,
+    complete: bool,
+}
+
+// Content-Type and body of a snapshot url, truncated to archive.max_size bytes.
+// The response must have the archived status when it is known, a 2xx otherwise.
+async fn get_archive_body(archive: &ArchiveConfig, timestampurl: &str, archived_status: Option<u16>) -> ArchiveBody {
+    let mut body = ArchiveBody {
+        content_type: None,
+        bytes: Vec::new(),
+        complete: false,
+    };
+    let response = match archive.client.get(timestampurl).send().await {
         Ok(resp) => resp,
         Err(err) => {
             eprintln!("Error while requesting {timestampurl} ({err}):");
-            return String::new();
+            return body;
         }
     };
+    let expected = match archived_status {
+        Some(status) => response.status().as_u16() == status,
+        None => response.status().is_success(),
+    };
+    if !expected {
+        eprintln!("Error while requesting {timestampurl} ({status})", status=response.status());
+        return body;
+    }
+    body.content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
 
-    let stream = response.bytes_stream();
-    let stream_reader = StreamReader::new(
-        stream.map_err(std::io::Error::other),
-    );
-    let mut lines = FramedRead::new(stream_reader, LinesCodec::new());
-
-    let mut content = String::new();
-    while let Some(line) = lines.next().await {
-        match line {
-            Ok(l) => {
-                content.push_str(&l);
-                content.push('\n');
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(chunk) => {
+                let left = usize::try_from(archive.max_size).unwrap_or(usize::MAX) - body.bytes.len();
+                if chunk.len() > left {
+                    body.bytes.extend_from_slice(&chunk[..left]);
+                    eprintln!("{timestampurl} is bigger than {max} bytes, truncated", max=archive.max_size);
+                    return body;
+                }
+                body.bytes.extend_from_slice(&chunk);
             }
-            Err(e) => {
-                eprintln!("Error reading line from {timestampurl}: {e}");
+            Err(err) => {
+                eprintln!("Error while reading {timestampurl} ({err})");
+                return body;
             }
         }
     }
+    body.complete = true;
+    body
+}
+
+// Snapshot url of an archive: the original payload (id_ modifier) unless
+// the rendered view is asked, where wayback adds its toolbar and rewrites links.
+fn snapshot_url(archive: &ArchiveConfig, url: &str, timestamp: &str) -> String {
+    if archive.rendered {
+        format!("http://web.archive.org/web/{timestamp}/{url}")
+    } else {
+        format!("http://web.archive.org/web/{timestamp}id_/{url}")
+    }
+}
 
-    content
+// Content of an archive, text transcoded to UTF-8 or binary left untouched.
+async fn get_archive_snapshot(archive: &ArchiveConfig, url: &str, timestamp: &str) -> snapshot::Content {
+    let timestampurl = snapshot_url(archive, url, timestamp);
+    let body = get_archive_body(archive, &timestampurl, None).await;
+    snapshot::decode(body.bytes, body.content_type.as_deref())
 }
 
-async fn http_status_urls_delay(
-    urls: Vec<String>,
-    delay: u64,
-    color: bool,
-    verbose: bool,
-    blacklist_code: &[u16],
-    whitelist_code: &[u16],
-) -> String {
-    if verbose {
+// Text content of an archive (empty for binary archives)
+async fn get_archive_content(archive: &ArchiveConfig, url: String, timestamp: String) -> String {
+    get_archive_snapshot(archive, &url, &timestamp).await.text()
+}
+
+async fn http_status_urls_delay(urls: Vec<String>, config: &UrlConfig) -> String {
+    if config.verbose {
         println!("We're checking status of {len} urls... ", len=urls.len());
     };
     let mut ret: String = String::new();
 
-    let client = reqwest::ClientBuilder::new()
-        .redirect(redirect::Policy::none())
-        .build()
-        .unwrap();
+    let client = no_redirect_client();
 
     for url in urls {
-        match client.get(&url).send().await {
+        match status_request(&client, &url, config).send().await {
             Ok(response) => {
-                if delay > 0 {
-                    let delay_time = time::Duration::from_millis(delay);
+                if config.delay > 0 {
+                    let delay_time = time::Duration::from_millis(config.delay);
                     sleep(delay_time).await;
                 }
-                if (whitelist_code.is_empty()
-                    || (whitelist_code)
-                        .iter()
-                        .any(|code| *code == response.status().as_u16()))
-                    && !(blacklist_code)
-                        .iter()
-                        .any(|code| *code == response.status().as_u16())
-                {
-                    let str_output = if color {
-                        format!("{url} {colorized}\n", url=&url, colorized=colorize(&response))
-                    } else if response.status().is_redirection() {
-                        format!(
-                            "{url} {status} to {location}\n",
-                            url=&url,
-                            status=&response.status(),
-                            location=&response.headers().get(LOCATION).unwrap_or(&HeaderValue::from_str("").unwrap()).to_str().unwrap()
-                        )
-                    } else {
-                        format!("{url} {status}\n", url=&url, status=&response.status())
-                    };
-
+                if let Some(str_output) = handle_status_response(response, &url, config).await {
                     print!("{str_output}");
                     ret.push_str(&str_output);
                 }
@@ -625,55 +1918,31 @@ async fn http_status_urls_delay(
     ret
 }
 
-async fn http_status_urls_no_delay(
-    urls: Vec<String>,
-    color: bool,
-    verbose: bool,
-    workers: usize,
-    blacklist_code: &[u16],
-    whitelist_code: &[u16],
-) -> String {
-    if verbose {
+async fn http_status_urls_no_delay(urls: Vec<String>, config: &UrlConfig) -> String {
+    if config.verbose {
         println!("We're checking status of {len} urls... ", len=urls.len());
     };
-    let client = reqwest::ClientBuilder::new()
-        .redirect(redirect::Policy::none())
-        .build()
-        .unwrap();
+    let client = no_redirect_client();
+    let client = &client;
     let mut bodies = stream::iter(urls)
-        .map(|url| async { (client.get(&url).send().await, url) })
-        .buffer_unordered(workers);
+        .map(|url| async move {
+            match status_request(client, &url, config).send().await {
+                Ok(response) => Ok(handle_status_response(response, &url, config).await),
+                Err(e) => Err(e),
+            }
+        })
+        .buffer_unordered(config.workers);
     let mut ret: String = String::new();
 
     while let Some(b) = bodies.next().await {
-        match b.0 {
-            Ok(response) => {
-                if (whitelist_code.is_empty()
-                    || (whitelist_code)
-                        .iter()
-                        .any(|code| *code == response.status().as_u16()))
-                    && !(blacklist_code)
-                        .iter()
-                        .any(|code| *code == response.status().as_u16())
-                {
-                    let str_output = if color {
-                        format!("{b1} {colorized}\n", b1=&b.1, colorized=colorize(&response))
-                    } else if response.status().is_redirection() {
-                        format!(
-                            "{b1} {status} to {location}\n",
-                            b1=&b.1,
-                            status=&response.status(),
-                            location=&response.headers().get(LOCATION).unwrap_or(&HeaderValue::from_str("").unwrap()).to_str().unwrap()
-                        )
-                    } else {
-                        format!("{b1} {status}\n", b1=&b.1, status=&response.status())
-                    };
-                    print!("{str_output}");
-                    ret.push_str(&str_output);
-                }
+        match b {
+            Ok(Some(str_output)) => {
+                print!("{str_output}");
+                ret.push_str(&str_output);
             }
+            Ok(None) => {}
             Err(e) => {
-                if verbose {
+                if config.verbose {
                     eprintln!("{e}");
                 }
             }
@@ -683,6 +1952,52 @@ async fn http_status_urls_no_delay(
     ret
 }
 
+fn no_redirect_client() -> reqwest::Client {
+    reqwest::ClientBuilder::new()
+        .redirect(redirect::Policy::none())
+        .build()
+        .unwrap()
+}
+
+fn status_request(client: &reqwest::Client, url: &str, config: &UrlConfig) -> reqwest::RequestBuilder {
+    let request = client.get(url);
+    if config.header_audit.is_some() {
+        request.header(ORIGIN, audit::AUDIT_ORIGIN)
+    } else {
+        request
+    }
+}
+
+// Format the status line of a checked url, or None if its status code is filtered out.
+// The response is consumed so it can be stored when --store-responses is set.
+async fn handle_status_response(response: Response, url: &str, config: &UrlConfig) -> Option<String> {
+    if let Some(header_audit) = &config.header_audit {
+        header_audit.lock().unwrap().record(url, &response);
+    }
+    let status = response.status().as_u16();
+    if !(config.whitelist_code.is_empty() || config.whitelist_code.contains(&status))
+        || config.blacklist_code.contains(&status)
+    {
+        return None;
+    }
+    let tags = url_tags(url, config);
+    let str_output = if config.color {
+        format!("{url} {colorized}{tags}\n", colorized=colorize(&response))
+    } else if response.status().is_redirection() {
+        format!(
+            "{url} {status} to {location}{tags}\n",
+            status=&response.status(),
+            location=&response.headers().get(LOCATION).unwrap_or(&HeaderValue::from_str("").unwrap()).to_str().unwrap()
+        )
+    } else {
+        format!("{url} {status}{tags}\n", status=&response.status())
+    };
+    if let Some(dir) = &config.store_responses {
+        store::store_response(dir, url, response).await;
+    }
+    Some(str_output)
+}
+
 fn colorize(response: &Response) -> String {
     let status = response.status().to_string();
 
@@ -0,0 +1,87 @@
+use crate::store::sanitize;
+use reqwest::Url;
+use serde_json::json;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+// One line of the CDX api (timestamp, original, mimetype, statuscode, digest).
+pub struct Capture {
+    pub timestamp: String,
+    pub original: String,
+    pub mimetype: String,
+    pub statuscode: String,
+    pub digest: String,
+}
+
+impl Capture {
+    pub fn parse(line: &str) -> Option<Capture> {
+        match line.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            [timestamp, original, mimetype, statuscode, digest] => Some(Capture {
+                timestamp: timestamp.to_string(),
+                original: original.to_string(),
+                mimetype: mimetype.to_string(),
+                statuscode: statuscode.to_string(),
+                digest: digest.to_string(),
+            }),
+            _ => None,
+        }
+    }
+
+    // dir/host/path/<timestamp>, urls with a query get their own directory
+    // (last segment followed by the hash of the query).
+    pub fn path(&self, dir: &Path) -> PathBuf {
+        let mut path = dir.to_path_buf();
+        match Url::parse(&self.original) {
+            Ok(parsed) => {
+                let host = match parsed.port() {
+                    Some(port) => format!("{}_{port}", parsed.host_str().unwrap_or("nohost")),
+                    None => parsed.host_str().unwrap_or("nohost").to_string(),
+                };
+                path.push(sanitize(&host));
+                let mut segments: Vec<String> = parsed
+                    .path_segments()
+                    .map(|segments| segments.filter(|s| !s.is_empty()).map(sanitize).collect())
+                    .unwrap_or_default();
+                if let Some(query) = parsed.query() {
+                    let mut hasher = DefaultHasher::new();
+                    query.hash(&mut hasher);
+                    let last = segments.pop().unwrap_or_else(|| "index".to_string());
+                    segments.push(format!("{last}-{hash:08x}", hash = hasher.finish() as u32));
+                }
+                for segment in segments {
+                    path.push(segment);
+                }
+            }
+            Err(_) => path.push("invalid"),
+        }
+        path.push(&self.timestamp);
+        path
+    }
+
+    fn metadata(&self) -> String {
+        let metadata = json!({
+            "url": self.original,
+            "timestamp": self.timestamp,
+            "mimetype": self.mimetype,
+            "statuscode": self.statuscode,
+            "digest": self.digest,
+        });
+        serde_json::to_string_pretty(&metadata).expect("Error serializing the metadata")
+    }
+}
+
+// The sidecar is written last, so a capture interrupted mid-write is fetched again.
+pub fn is_saved(path: &Path) -> bool {
+    path.is_file() && path.with_extension("json").is_file()
+}
+
+// Write the content of a capture and its <timestamp>.json metadata sidecar.
+pub fn save(path: &Path, capture: &Capture, content: &[u8]) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("Error creating the directory");
+    }
+    fs::write(path, content).expect("Error writing the capture to the file");
+    fs::write(path.with_extension("json"), capture.metadata()).expect("Error writing the metadata file");
+}
@@ -0,0 +1,224 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+const RULES: [&str; 6] = ["host", "port", "slash", "encoding", "fragment", "query"];
+
+// Canonicalizes urls with the selected rules and remembers every canonical url
+// so duplicates are dropped across all the domains of a run.
+pub struct Normalizer {
+    rules: Vec<String>,
+    seen: Mutex<HashSet<String>>,
+}
+
+impl Normalizer {
+    // rules is a comma separated list of RULES, or "all"
+    pub fn new(rules: &str) -> Result<Normalizer, String> {
+        let rules: Vec<String> = if rules == "all" {
+            RULES.iter().map(|rule| rule.to_string()).collect()
+        } else {
+            rules.split(',').map(|rule| rule.trim().to_lowercase()).collect()
+        };
+        if let Some(unknown) = rules.iter().find(|rule| !RULES.contains(&rule.as_str())) {
+            return Err(format!(
+                "unknown normalization rule '{unknown}' (available: all,{})",
+                RULES.join(",")
+            ));
+        }
+        Ok(Normalizer {
+            rules,
+            seen: Mutex::new(HashSet::new()),
+        })
+    }
+
+    // Canonical form of the urls, without the ones already seen in this run.
+    pub fn normalize_urls(&self, urls: Vec<String>) -> Vec<String> {
+        let mut seen = self.seen.lock().unwrap();
+        urls.into_iter()
+            .map(|url| self.normalize(&url))
+            .filter(|url| seen.insert(url.clone()))
+            .collect()
+    }
+
+    fn has(&self, rule: &str) -> bool {
+        self.rules.iter().any(|r| r == rule)
+    }
+
+    fn normalize(&self, url: &str) -> String {
+        let (rest, fragment) = match url.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (url, None),
+        };
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (rest, None),
+        };
+        let (scheme, rest) = match rest.split_once("://") {
+            Some((scheme, rest)) => (scheme.to_string(), rest),
+            None => (String::new(), rest),
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, ""),
+        };
+
+        let mut authority = authority.to_string();
+        let mut scheme = scheme;
+        if self.has("host") {
+            scheme = scheme.to_lowercase();
+            authority = authority.to_lowercase();
+        }
+        if self.has("port") {
+            let default_port = match scheme.to_lowercase().as_str() {
+                "http" => Some(":80"),
+                "https" => Some(":443"),
+                _ => None,
+            };
+            if let Some(port) = default_port {
+                if let Some(stripped) = authority.strip_suffix(port) {
+                    authority = stripped.to_string();
+                }
+            }
+        }
+
+        let mut path = path.to_string();
+        if self.has("encoding") {
+            path = normalize_encoding(&path);
+        }
+        if self.has("slash") {
+            let trimmed = path.trim_end_matches('/');
+            path = if trimmed.is_empty() { "/".to_string() } else { trimmed.to_string() };
+        }
+
+        let mut query = query.map(String::from);
+        if let Some(q) = &query {
+            // empty pairs (a&&b) are only dropped by the query rule
+            let mut params: Vec<String> = q
+                .split('&')
+                .filter(|param| !param.is_empty() || !self.has("query"))
+                .map(|param| {
+                    if self.has("encoding") {
+                        normalize_encoding(param)
+                    } else {
+                        param.to_string()
+                    }
+                })
+                .collect();
+            if self.has("query") {
+                params.sort();
+            }
+            query = if params.is_empty() && self.has("query") { None } else { Some(params.join("&")) };
+        }
+
+        let mut normalized = if scheme.is_empty() {
+            format!("{authority}{path}")
+        } else {
+            format!("{scheme}://{authority}{path}")
+        };
+        if let Some(query) = query {
+            normalized.push('?');
+            normalized.push_str(&query);
+        }
+        if let Some(fragment) = fragment {
+            if !self.has("fragment") {
+                normalized.push('#');
+                normalized.push_str(fragment);
+            }
+        }
+        normalized
+    }
+}
+
+// Decode percent-escapes of unreserved characters and uppercase the others,
+// so %7e, %7E and ~ all end up as ~.
+fn normalize_encoding(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut ret = String::with_capacity(input.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit()
+        {
+            let hex = &input[i + 1..i + 3];
+            let c = u8::from_str_radix(hex, 16).unwrap() as char;
+            if c.is_ascii_alphanumeric() || "-._~".contains(c) {
+                ret.push(c);
+            } else {
+                ret.push('%');
+                ret.push_str(&hex.to_uppercase());
+            }
+            i += 3;
+        } else {
+            let c = input[i..].chars().next().unwrap();
+            ret.push(c);
+            i += c.len_utf8();
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(rules: &str, url: &str) -> String {
+        Normalizer::new(rules).unwrap().normalize(url)
+    }
+
+    #[test]
+    fn host_rule_lowercases_scheme_and_host_only() {
+        assert_eq!(normalize("host", "HTTPS://Example.COM/Path?Q=A"), "https://example.com/Path?Q=A");
+    }
+
+    #[test]
+    fn port_rule_strips_default_ports() {
+        assert_eq!(normalize("port", "http://example.com:80/a"), "http://example.com/a");
+        assert_eq!(normalize("port", "https://example.com:443/a"), "https://example.com/a");
+        assert_eq!(normalize("port", "http://example.com:443/a"), "http://example.com:443/a");
+        assert_eq!(normalize("port", "https://example.com:8443/a"), "https://example.com:8443/a");
+    }
+
+    #[test]
+    fn slash_rule() {
+        assert_eq!(normalize("slash", "http://example.com"), "http://example.com/");
+        assert_eq!(normalize("slash", "http://example.com/"), "http://example.com/");
+        assert_eq!(normalize("slash", "http://example.com/a/"), "http://example.com/a");
+        assert_eq!(normalize("host", "http://example.com"), "http://example.com");
+    }
+
+    #[test]
+    fn encoding_rule() {
+        assert_eq!(normalize("encoding", "http://example.com/%7euser"), "http://example.com/~user");
+        assert_eq!(normalize("encoding", "http://example.com/~user"), "http://example.com/~user");
+        assert_eq!(normalize("encoding", "http://example.com/a%2fb?q=%3c"), "http://example.com/a%2Fb?q=%3C");
+        assert_eq!(normalize("host", "http://example.com/%7euser"), "http://example.com/%7euser");
+    }
+
+    #[test]
+    fn fragment_rule() {
+        assert_eq!(normalize("fragment", "http://example.com/a?b=1#top"), "http://example.com/a?b=1");
+        assert_eq!(normalize("host", "http://example.com/a#top"), "http://example.com/a#top");
+    }
+
+    #[test]
+    fn query_rule_sorts_and_drops_empty_pairs() {
+        assert_eq!(normalize("query", "http://example.com/?b=2&&a=1"), "http://example.com/?a=1&b=2");
+        assert_eq!(normalize("query", "http://example.com/?"), "http://example.com/");
+        assert_eq!(normalize("host", "http://example.com/?b=2&&a=1"), "http://example.com/?b=2&&a=1");
+    }
+
+    #[test]
+    fn normalize_urls_dedups_across_calls() {
+        let normalizer = Normalizer::new("all").unwrap();
+        let first = normalizer.normalize_urls(vec!["http://Example.com:80/a/?b=1&a=2".to_string()]);
+        let second = normalizer.normalize_urls(vec!["http://example.com/a?a=2&b=1#x".to_string()]);
+        assert_eq!(first, vec!["http://example.com/a?a=2&b=1"]);
+        assert!(second.is_empty());
+    }
+
+    #[test]
+    fn unknown_rule() {
+        assert!(Normalizer::new("host,bogus").is_err());
+    }
+}
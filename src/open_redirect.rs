@@ -0,0 +1,71 @@
+use reqwest::header::LOCATION;
+use reqwest::{Client, Url};
+
+// Reserved TLD (RFC 2606), it can never belong to a real third party.
+const CANARY_HOST: &str = "wbr-canary.example";
+
+const REDIRECT_PARAMS: [&str; 28] = [
+    "next", "url", "uri", "redirect", "redirect_uri", "redirect_url", "redirecturl", "redir",
+    "return", "returnto", "return_to", "returnurl", "return_url", "goto", "go", "dest",
+    "destination", "continue", "target", "to", "out", "view", "callback", "forward",
+    "location", "rurl", "success_url", "checkout_url",
+];
+
+// Substitute the canary url in every redirect-like parameter (one request per
+// parameter) and report the responses whose Location points to the canary host.
+pub async fn check_open_redirects(urls: &[String], client: &Client, workers: usize, delay: u64, verbose: bool) -> String {
+    let probes: Vec<(String, String, Url)> = urls.iter().flat_map(|url| build_probes(url)).collect();
+    if verbose {
+        println!("Checking {len} redirect parameters for open redirects... ", len = probes.len());
+    }
+
+    crate::probe::run_probes(probes, workers, delay, verbose, |(url, param, probe)| async move {
+        let response = client.get(probe.clone()).send().await?;
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .map(String::from);
+        Ok(match location {
+            Some(location) if redirects_to_canary(&probe, &location) => {
+                format!("{url} param={param} Location: {location}\n")
+            }
+            _ => String::new(),
+        })
+    })
+    .await
+}
+
+fn redirects_to_canary(probe: &Url, location: &str) -> bool {
+    probe
+        .join(location)
+        .map(|target| target.host_str() == Some(CANARY_HOST))
+        .unwrap_or(false)
+}
+
+// One probe per redirect-like parameter of the url.
+fn build_probes(url: &str) -> Vec<(String, String, Url)> {
+    let parsed = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(_) => return Vec::new(),
+    };
+    let pairs: Vec<(String, String)> = parsed.query_pairs().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    let canary = format!("https://{CANARY_HOST}/");
+
+    let mut probes = Vec::new();
+    for (i, (name, _)) in pairs.iter().enumerate() {
+        if !REDIRECT_PARAMS.contains(&name.to_lowercase().as_str()) {
+            continue;
+        }
+        let mut probe = parsed.clone();
+        probe.query_pairs_mut().clear().extend_pairs(pairs.iter().enumerate().map(|(j, (k, v))| {
+            if i == j {
+                (k.as_str(), canary.as_str())
+            } else {
+                (k.as_str(), v.as_str())
+            }
+        }));
+        probes.push((url.to_string(), name.clone(), probe));
+    }
+    probes
+}
@@ -0,0 +1,147 @@
+use reqwest::Url;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Number of distinct values kept as sample for each parameter.
+const SAMPLE_VALUES: usize = 10;
+
+#[derive(Default)]
+struct Parameter {
+    count: usize,
+    endpoints: BTreeSet<String>,
+    values: BTreeSet<String>,
+    types: BTreeMap<&'static str, usize>,
+}
+
+// Every query parameter seen in the archived urls of a domain.
+#[derive(Default)]
+pub struct ParamInventory {
+    parameters: BTreeMap<String, Parameter>,
+}
+
+impl ParamInventory {
+    pub fn add_url(&mut self, url: &str) {
+        let parsed = match Url::parse(url) {
+            Ok(parsed) => parsed,
+            Err(_) => return,
+        };
+        let endpoint = format!("{host}{path}", host = parsed.host_str().unwrap_or(""), path = parsed.path());
+        for (name, value) in parsed.query_pairs() {
+            let parameter = self.parameters.entry(name.to_string()).or_default();
+            parameter.count += 1;
+            parameter.endpoints.insert(endpoint.clone());
+            if !value.is_empty() {
+                *parameter.types.entry(value_type(&value)).or_insert(0) += 1;
+                if parameter.values.len() < SAMPLE_VALUES {
+                    parameter.values.insert(value.to_string());
+                }
+            }
+        }
+    }
+
+    // Parameters that carried urls are flagged as ssrf leads and the ones that
+    // carried paths as lfi leads.
+    pub fn to_json(&self) -> Value {
+        let parameters: Vec<Value> = self
+            .parameters
+            .iter()
+            .map(|(name, parameter)| {
+                let value_type = parameter
+                    .types
+                    .iter()
+                    .max_by_key(|(_, count)| **count)
+                    .map(|(value_type, _)| *value_type)
+                    .unwrap_or("empty");
+                let lead = parameter.lead();
+                json!({
+                    "name": name,
+                    "type": value_type,
+                    "lead": lead,
+                    "count": parameter.count,
+                    "endpoints": parameter.endpoints,
+                    "values": parameter.values,
+                })
+            })
+            .collect();
+        Value::Array(parameters)
+    }
+
+    // (parameter, lead) for every parameter flagged as a lead
+    pub fn leads(&self) -> Vec<(&str, &'static str)> {
+        self.parameters
+            .iter()
+            .filter_map(|(name, parameter)| parameter.lead().map(|lead| (name.as_str(), lead)))
+            .collect()
+    }
+}
+
+impl Parameter {
+    fn lead(&self) -> Option<&'static str> {
+        if self.types.contains_key("url") {
+            Some("ssrf")
+        } else if self.types.contains_key("path") {
+            Some("lfi")
+        } else {
+            None
+        }
+    }
+}
+
+fn value_type(value: &str) -> &'static str {
+    let lower = value.to_lowercase();
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+        "int"
+    } else if lower.starts_with("//") || lower.contains("://") {
+        "url"
+    } else if is_email(value) {
+        "email"
+    } else if is_jwt(value) {
+        "jwt"
+    } else if is_path(value) {
+        "path"
+    } else if is_base64(value) {
+        "base64"
+    } else {
+        "string"
+    }
+}
+
+fn is_email(value: &str) -> bool {
+    match value.split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty() && domain.contains('.') && !value.contains(char::is_whitespace) && !domain.contains('@')
+        }
+        None => false,
+    }
+}
+
+fn is_jwt(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('.').collect();
+    parts.len() == 3
+        && value.starts_with("eyJ")
+        && parts
+            .iter()
+            .all(|part| part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+}
+
+fn is_path(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let windows_drive = bytes.len() > 2 && bytes[1] == b':' && (bytes[2] == b'\\' || bytes[2] == b'/');
+    value.starts_with('/')
+        || value.starts_with("./")
+        || value.contains("../")
+        || value.contains("..\\")
+        || windows_drive
+        || (value.contains('/') && value.rsplit('/').next().unwrap_or("").contains('.'))
+}
+
+fn is_base64(value: &str) -> bool {
+    value.len() >= 12
+        && value.len().is_multiple_of(4)
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/' || c == '=')
+        && value.trim_end_matches('=').chars().all(|c| c != '=')
+        && (value.chars().any(|c| c.is_ascii_digit()) || value.ends_with('='))
+}
@@ -0,0 +1,40 @@
+use futures::{stream, Future, StreamExt};
+use std::time::Duration;
+use tokio::time::sleep;
+
+// Run the active probes of --reflect and --open-redirect: at most workers at
+// a time, or one by one delay ms apart when a delay is set. check sends a probe
+// and returns the lines to report, which are printed as they come.
+pub async fn run_probes<P, F, Fut>(probes: Vec<P>, workers: usize, delay: u64, verbose: bool, check: F) -> String
+where
+    F: Fn(P) -> Fut,
+    Fut: Future<Output = Result<String, reqwest::Error>>,
+{
+    let concurrency = if delay > 0 { 1 } else { workers.max(1) };
+    let check = &check;
+    let mut results = stream::iter(probes)
+        .map(|probe| async move {
+            let result = check(probe).await;
+            if delay > 0 {
+                sleep(Duration::from_millis(delay)).await;
+            }
+            result
+        })
+        .buffer_unordered(concurrency);
+
+    let mut ret = String::new();
+    while let Some(result) = results.next().await {
+        match result {
+            Ok(found) => {
+                print!("{found}");
+                ret.push_str(&found);
+            }
+            Err(e) => {
+                if verbose {
+                    eprintln!("{e}");
+                }
+            }
+        }
+    }
+    ret
+}
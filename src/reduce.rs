@@ -0,0 +1,58 @@
+use reqwest::Url;
+use std::collections::{BTreeSet, HashSet};
+
+const STATIC_EXTENSIONS: [&str; 26] = [
+    "css", "png", "jpg", "jpeg", "gif", "svg", "ico", "webp", "bmp", "tif", "tiff", "woff",
+    "woff2", "ttf", "eot", "otf", "mp3", "mp4", "avi", "mov", "webm", "flv", "wav", "ogg",
+    "m4a", "m4v",
+];
+
+// Keep one url per (host, path, set of parameter names), and drop static assets
+// and parameterless paths that look like content (blog slugs, dated articles).
+pub fn reduce_urls(urls: Vec<String>) -> Vec<String> {
+    let mut seen: HashSet<(String, String, BTreeSet<String>)> = HashSet::new();
+    urls.into_iter()
+        .filter(|url| {
+            let parsed = match Url::parse(url) {
+                Ok(parsed) => parsed,
+                Err(_) => return false,
+            };
+            let params: BTreeSet<String> = parsed.query_pairs().map(|(name, _)| name.to_string()).collect();
+            let path = parsed.path().to_string();
+            if is_static_asset(&path) || (params.is_empty() && is_content_path(&path)) {
+                return false;
+            }
+            let host = parsed.host_str().unwrap_or("").to_lowercase();
+            seen.insert((host, path, params))
+        })
+        .collect()
+}
+
+fn is_static_asset(path: &str) -> bool {
+    let filename = path.rsplit('/').next().unwrap_or("");
+    match filename.rsplit_once('.') {
+        Some((_, extension)) => STATIC_EXTENSIONS.contains(&extension.to_lowercase().as_str()),
+        None => false,
+    }
+}
+
+// /2019/05/some-title, /blog/how-we-moved-to-rust, ...
+fn is_content_path(path: &str) -> bool {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let dated = segments.windows(2).any(|pair| {
+        pair[0].len() == 4
+            && pair[0].chars().all(|c| c.is_ascii_digit())
+            && (1..=2).contains(&pair[1].len())
+            && pair[1].chars().all(|c| c.is_ascii_digit())
+    });
+    dated || segments.iter().any(|segment| is_slug(segment))
+}
+
+// At least four words joined by hyphens, long enough to be an article title.
+fn is_slug(segment: &str) -> bool {
+    let stem = segment.split('.').next().unwrap_or(segment);
+    let words: Vec<&str> = stem.split('-').collect();
+    words.len() >= 4
+        && stem.len() >= 25
+        && words.iter().all(|word| !word.is_empty() && word.chars().all(|c| c.is_alphanumeric()))
+}
@@ -0,0 +1,103 @@
+use reqwest::{Client, Url};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// An archived url with every parameter value replaced by its own canary.
+struct Probe {
+    url: String,
+    probe: Url,
+    canaries: Vec<(String, String)>,
+}
+
+// Send each parameterized url once with canary values and report, for every
+// parameter, where its canary comes back (header, script, attribute or html).
+pub async fn check_reflections(urls: &[String], client: &Client, workers: usize, delay: u64, verbose: bool) -> String {
+    let nonce = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() % 0xffffff)
+        .unwrap_or(0);
+    let probes: Vec<Probe> = urls
+        .iter()
+        .enumerate()
+        .filter_map(|(i, url)| build_probe(url, format!("wbr{nonce:x}u{i}p").as_str()))
+        .collect();
+    if verbose {
+        println!("Checking reflections in {len} parameterized urls... ", len = probes.len());
+    }
+
+    crate::probe::run_probes(probes, workers, delay, verbose, |probe| async move {
+        let response = client.get(probe.probe.clone()).send().await?;
+        let headers: Vec<(String, String)> = response
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).to_string()))
+            .collect();
+        let body = response.text().await.unwrap_or_default();
+        Ok(find_reflections(&probe, &headers, &body))
+    })
+    .await
+}
+
+fn build_probe(url: &str, canary_prefix: &str) -> Option<Probe> {
+    let parsed = Url::parse(url).ok()?;
+    let params: Vec<String> = parsed.query_pairs().map(|(name, _)| name.to_string()).collect();
+    if params.is_empty() {
+        return None;
+    }
+    let canaries: Vec<(String, String)> = params
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| (name, format!("{canary_prefix}{i}x")))
+        .collect();
+    let mut probe = parsed.clone();
+    probe
+        .query_pairs_mut()
+        .clear()
+        .extend_pairs(canaries.iter().map(|(name, canary)| (name.as_str(), canary.as_str())));
+    Some(Probe {
+        url: url.to_string(),
+        probe,
+        canaries,
+    })
+}
+
+fn find_reflections(probe: &Probe, headers: &[(String, String)], body: &str) -> String {
+    let lower = body.to_lowercase();
+    let mut ret = String::new();
+    for (param, canary) in &probe.canaries {
+        let mut contexts: Vec<String> = headers
+            .iter()
+            .filter(|(_, value)| value.to_lowercase().contains(canary.as_str()))
+            .map(|(name, _)| format!("header({name})"))
+            .collect();
+        for (pos, _) in lower.match_indices(canary.as_str()) {
+            let context = body_context(&lower, pos).to_string();
+            if !contexts.contains(&context) {
+                contexts.push(context);
+            }
+        }
+        if !contexts.is_empty() {
+            ret.push_str(
+                format!("{url} param={param} reflected in {contexts}\n", url = probe.url, contexts = contexts.join(","))
+                    .as_str(),
+            );
+        }
+    }
+    ret
+}
+
+// Context of a reflection found at pos in the lowercased body.
+fn body_context(lower: &str, pos: usize) -> &'static str {
+    let before = &lower[..pos];
+    if let Some(open) = before.rfind('<') {
+        let is_tag = lower[open + 1..].starts_with(|c: char| c.is_ascii_alphabetic());
+        if is_tag && before.rfind('>').is_none_or(|close| close < open) {
+            return "attribute";
+        }
+    }
+    if let Some(open) = before.rfind("<script") {
+        if before.rfind("</script").is_none_or(|close| close < open) {
+            return "script";
+        }
+    }
+    "html"
+}
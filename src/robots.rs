@@ -0,0 +1,216 @@
+use serde_json::{json, Value};
+
+// One directive of a robots.txt. Group directives (allow, disallow,
+// crawl-delay, ...) keep the user-agents of their group, global ones
+// (sitemap) have none.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Entry {
+    pub directive: String,
+    pub value: String,
+    pub user_agents: Vec<String>,
+}
+
+impl Entry {
+    pub fn to_text(&self) -> String {
+        if self.user_agents.is_empty() {
+            format!("{}: {}", self.directive, self.value)
+        } else {
+            format!("{}: {} (user-agent: {})", self.directive, self.value, self.user_agents.join(", "))
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "directive": self.directive,
+            "value": self.value,
+            "user_agents": self.user_agents,
+        })
+    }
+
+    // allow and disallow values are path patterns
+    pub fn is_path_rule(&self) -> bool {
+        self.directive == "allow" || self.directive == "disallow"
+    }
+}
+
+// Parse a robots.txt into its directives. Consecutive user-agent lines open
+// a group that lasts until the next user-agent line following a rule.
+pub fn parse(content: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut user_agents: Vec<String> = Vec::new();
+    let mut group_has_rules = false;
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+        let directive: String = key
+            .to_lowercase()
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+            .collect();
+        let directive = match directive.as_str() {
+            "useragent" => {
+                if group_has_rules {
+                    user_agents.clear();
+                    group_has_rules = false;
+                }
+                if !value.is_empty() {
+                    user_agents.push(value.to_lowercase());
+                }
+                continue;
+            }
+            "allow" => "allow".to_string(),
+            "disallow" | "dissallow" | "disalow" => "disallow".to_string(),
+            "crawldelay" => "crawl-delay".to_string(),
+            "sitemap" | "sitemaps" => {
+                if !value.is_empty() {
+                    entries.push(Entry {
+                        directive: "sitemap".to_string(),
+                        value: value.to_string(),
+                        user_agents: Vec::new(),
+                    });
+                }
+                continue;
+            }
+            // html pages served as robots.txt have plenty of "key: value" looking lines
+            _ if key.contains(|c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '_') => continue,
+            _ => key.to_lowercase(),
+        };
+        // even empty, a rule closes the user-agent list of its group
+        group_has_rules = true;
+        // an empty disallow allows everything, it says nothing about the site
+        if value.is_empty() {
+            continue;
+        }
+        entries.push(Entry {
+            directive,
+            value: value.to_string(),
+            user_agents: user_agents.clone(),
+        });
+    }
+    entries
+}
+
+// An entry with the timestamps of the first and last snapshots containing it.
+pub struct SeenEntry {
+    pub entry: Entry,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+// Match a path (with its query) against an allow/disallow pattern: * matches
+// any sequence of characters and a trailing $ anchors the end of the path.
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern = absolute_pattern(pattern);
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern.as_str(), false),
+    };
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let mut rest = match path.strip_prefix(parts[0]) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    for (i, part) in parts.iter().enumerate().skip(1) {
+        if anchored && i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+// Literal beginning of a pattern, usable as a CDX prefix query.
+pub fn pattern_prefix(pattern: &str) -> String {
+    let pattern = absolute_pattern(pattern);
+    let end = pattern.find(['*', '$']).unwrap_or(pattern.len());
+    pattern[..end].to_string()
+}
+
+// Patterns are matched against paths, "admin" is read as "/admin".
+fn absolute_pattern(pattern: &str) -> String {
+    if pattern.starts_with('/') {
+        pattern.to_string()
+    } else {
+        format!("/{pattern}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(content: &str) -> Vec<String> {
+        parse(content).iter().map(Entry::to_text).collect()
+    }
+
+    #[test]
+    fn parse_groups_user_agents() {
+        let content = "User-agent: Googlebot\nUser-agent: Bingbot\nDisallow: /private/\nCrawl-delay: 5\n\nUser-agent: *\nAllow: /public/\n";
+        assert_eq!(
+            texts(content),
+            vec![
+                "disallow: /private/ (user-agent: googlebot, bingbot)",
+                "crawl-delay: 5 (user-agent: googlebot, bingbot)",
+                "allow: /public/ (user-agent: *)",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_empty_disallow_closes_group() {
+        let content = "User-agent: Googlebot\nDisallow:\n\nUser-agent: *\nDisallow: /\n";
+        assert_eq!(texts(content), vec!["disallow: / (user-agent: *)"]);
+    }
+
+    #[test]
+    fn parse_sitemap_url_with_colons() {
+        let content = "User-agent: *\nDisallow: /admin # comment\nSitemap: https://example.com:8443/sitemap.xml\n";
+        let entries = parse(content);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].directive, "sitemap");
+        assert_eq!(entries[1].value, "https://example.com:8443/sitemap.xml");
+        assert!(entries[1].user_agents.is_empty());
+    }
+
+    #[test]
+    fn path_matches_wildcards_and_anchors() {
+        assert!(path_matches("/*.bak$", "/backup/db.bak"));
+        assert!(!path_matches("/*.bak$", "/db.bak.old"));
+        assert!(!path_matches("/*.bak$", "/db.bak?v=1"));
+        assert!(path_matches("/a*$", "/a"));
+        assert!(path_matches("/a*$", "/admin/users"));
+        assert!(!path_matches("/a*$", "/b"));
+        assert!(path_matches("/$", "/"));
+        assert!(!path_matches("/$", "/index.html"));
+        assert!(path_matches("/admin", "/admin/login"));
+        assert!(path_matches("/*?id=", "/page?id=1"));
+    }
+
+    #[test]
+    fn patterns_without_leading_slash() {
+        assert!(path_matches("admin", "/admin/login"));
+        assert!(path_matches("*.php", "/index.php"));
+        assert_eq!(pattern_prefix("admin"), "/admin");
+        assert_eq!(pattern_prefix("*.php"), "/");
+    }
+
+    #[test]
+    fn pattern_prefix_stops_at_wildcards() {
+        assert_eq!(pattern_prefix("/private/*.bak$"), "/private/");
+        assert_eq!(pattern_prefix("/search$"), "/search");
+        assert_eq!(pattern_prefix("/static/"), "/static/");
+    }
+
+    #[test]
+    fn parse_ignores_html() {
+        let content = "<!DOCTYPE html>\n<html><head>\n<meta http-equiv=\"refresh\" content=\"0; url=https://example.com/\">\n<style>body { color: red; }</style>\n</head><body><a href=\"https://example.com/\">Not found: go home</a></body></html>\n";
+        assert!(parse(content).is_empty());
+    }
+}
@@ -0,0 +1,196 @@
+use reqwest::Url;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+enum Rule {
+    Host(String),
+    // *.example.com, matches the subdomains of example.com
+    Wildcard(String),
+    Cidr(IpAddr, u8),
+}
+
+// In-scope and out-of-scope assets of a bug bounty program.
+pub struct Scope {
+    include: Vec<Rule>,
+    exclude: Vec<Rule>,
+    dropped: AtomicUsize,
+}
+
+impl Scope {
+    // One entry per line: host, *.wildcard, url, IP or CIDR. Entries prefixed
+    // with ! or - are out of scope, as are the entries under an "out of scope:"
+    // line (until an "in scope:" line). # starts a comment.
+    pub fn from_file(path: &Path) -> Result<Scope, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {e}", path.display()))?;
+        let mut scope = Scope {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            dropped: AtomicUsize::new(0),
+        };
+        let mut out_of_scope_section = false;
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let lower = line.to_lowercase();
+            if lower.ends_with(':') {
+                let section = lower.replace(['-', '_', ' '], "");
+                if section.starts_with("outofscope") {
+                    out_of_scope_section = true;
+                    continue;
+                } else if section.starts_with("inscope") {
+                    out_of_scope_section = false;
+                    continue;
+                }
+            }
+            let (excluded, entry) = match lower.strip_prefix('!').or_else(|| lower.strip_prefix('-')) {
+                Some(entry) => (true, entry.trim()),
+                None => (out_of_scope_section, lower.as_str()),
+            };
+            let rule = parse_rule(entry).ok_or_else(|| format!("invalid scope entry in {}: {line}", path.display()))?;
+            if excluded {
+                scope.exclude.push(rule);
+            } else {
+                scope.include.push(rule);
+            }
+        }
+        Ok(scope)
+    }
+
+    // Drop (and count) the urls that are not in scope.
+    pub fn filter_urls(&self, urls: Vec<String>) -> Vec<String> {
+        let before = urls.len();
+        let kept: Vec<String> = urls.into_iter().filter(|url| self.is_in_scope(url)).collect();
+        self.dropped.fetch_add(before - kept.len(), Ordering::Relaxed);
+        kept
+    }
+
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn is_in_scope(&self, url: &str) -> bool {
+        let host = match Url::parse(url) {
+            Ok(parsed) => match parsed.host_str() {
+                Some(host) => host.trim_start_matches('[').trim_end_matches(']').to_lowercase(),
+                None => return false,
+            },
+            Err(_) => return false,
+        };
+        (self.include.is_empty() || self.include.iter().any(|rule| rule_matches(rule, &host)))
+            && !self.exclude.iter().any(|rule| rule_matches(rule, &host))
+    }
+}
+
+fn parse_rule(entry: &str) -> Option<Rule> {
+    if let Some((ip, prefix)) = entry.split_once('/') {
+        if let (Ok(ip), Ok(prefix)) = (ip.parse::<IpAddr>(), prefix.parse::<u8>()) {
+            let max = if ip.is_ipv4() { 32 } else { 128 };
+            return if prefix <= max { Some(Rule::Cidr(ip, prefix)) } else { None };
+        }
+    }
+    // urls are reduced to their host
+    let host = match entry.split_once("://") {
+        Some((_, rest)) => rest,
+        None => entry,
+    };
+    let host = host.split(['/', '?', '#']).next().unwrap_or("");
+    // the port is stripped after the brackets of an IPv6, and never from a bare one
+    let host = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or(""),
+        None if host.parse::<IpAddr>().is_ok() => host,
+        None => host.rsplit_once(':').map(|(host, _)| host).unwrap_or(host),
+    };
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        let prefix = if ip.is_ipv4() { 32 } else { 128 };
+        return Some(Rule::Cidr(ip, prefix));
+    }
+    if host.is_empty() {
+        None
+    } else if let Some(domain) = host.strip_prefix("*.") {
+        Some(Rule::Wildcard(domain.to_string()))
+    } else {
+        Some(Rule::Host(host.to_string()))
+    }
+}
+
+fn rule_matches(rule: &Rule, host: &str) -> bool {
+    match rule {
+        Rule::Host(expected) => host == expected,
+        Rule::Wildcard(domain) => host.ends_with(format!(".{domain}").as_str()),
+        Rule::Cidr(network, prefix) => match host.parse::<IpAddr>() {
+            Ok(ip) => in_cidr(ip, *network, *prefix),
+            Err(_) => false,
+        },
+    }
+}
+
+fn in_cidr(ip: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = if prefix == 0 { 0 } else { u32::MAX << (32 - prefix) };
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = if prefix == 0 { 0 } else { u128::MAX << (128 - prefix) };
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_rule_hosts_and_wildcards() {
+        assert!(matches!(parse_rule("example.com"), Some(Rule::Host(host)) if host == "example.com"));
+        assert!(matches!(parse_rule("https://example.com:8443/login"), Some(Rule::Host(host)) if host == "example.com"));
+        assert!(matches!(parse_rule("*.example.com"), Some(Rule::Wildcard(domain)) if domain == "example.com"));
+        assert!(parse_rule("https://").is_none());
+    }
+
+    #[test]
+    fn parse_rule_ips() {
+        assert!(matches!(parse_rule("10.0.0.1"), Some(Rule::Cidr(network, 32)) if network == ip("10.0.0.1")));
+        assert!(matches!(parse_rule("http://10.0.0.1:8080/"), Some(Rule::Cidr(network, 32)) if network == ip("10.0.0.1")));
+        assert!(matches!(parse_rule("10.0.0.0/8"), Some(Rule::Cidr(network, 8)) if network == ip("10.0.0.0")));
+        assert!(parse_rule("10.0.0.0/33").is_none());
+        assert!(matches!(parse_rule("2001:db8::1"), Some(Rule::Cidr(network, 128)) if network == ip("2001:db8::1")));
+        assert!(matches!(parse_rule("[2001:db8::1]"), Some(Rule::Cidr(network, 128)) if network == ip("2001:db8::1")));
+        assert!(matches!(parse_rule("http://[2001:db8::1]:8080/"), Some(Rule::Cidr(network, 128)) if network == ip("2001:db8::1")));
+        assert!(matches!(parse_rule("2001:db8::/32"), Some(Rule::Cidr(_, 32))));
+    }
+
+    #[test]
+    fn in_cidr_prefixes() {
+        assert!(in_cidr(ip("203.0.113.7"), ip("0.0.0.0"), 0));
+        assert!(in_cidr(ip("10.1.2.3"), ip("10.1.2.3"), 32));
+        assert!(!in_cidr(ip("10.1.2.4"), ip("10.1.2.3"), 32));
+        assert!(in_cidr(ip("10.200.0.1"), ip("10.0.0.0"), 8));
+        assert!(in_cidr(ip("2001:db8::1"), ip("::"), 0));
+        assert!(in_cidr(ip("2001:db8::1"), ip("2001:db8::1"), 128));
+        assert!(!in_cidr(ip("2001:db8::2"), ip("2001:db8::1"), 128));
+        assert!(!in_cidr(ip("10.0.0.1"), ip("::"), 0));
+        assert!(!in_cidr(ip("::ffff:10.0.0.1"), ip("10.0.0.0"), 8));
+    }
+
+    #[test]
+    fn rules_match_hosts() {
+        let wildcard = parse_rule("*.example.com").unwrap();
+        assert!(rule_matches(&wildcard, "api.example.com"));
+        assert!(rule_matches(&wildcard, "a.b.example.com"));
+        assert!(!rule_matches(&wildcard, "example.com"));
+        assert!(!rule_matches(&wildcard, "badexample.com"));
+        let ipv6 = parse_rule("http://[2001:db8::1]:8080/").unwrap();
+        assert!(rule_matches(&ipv6, "2001:db8::1"));
+    }
+}
@@ -0,0 +1,95 @@
+use flate2::read::GzDecoder;
+use std::io::Read;
+
+pub enum Sitemap {
+    // <sitemapindex>: locations of other sitemaps
+    Index(Vec<String>),
+    // <urlset>: (loc, lastmod) of each url
+    UrlSet(Vec<(String, Option<String>)>),
+}
+
+// Parse a sitemap or sitemap index, gzipped or not. Gzipped sitemaps are
+// decompressed up to max_size bytes, the rest is truncated.
+pub fn parse(content: &[u8], max_size: u64) -> Sitemap {
+    let xml = if content.starts_with(&[0x1f, 0x8b]) {
+        let mut decoded = Vec::new();
+        // one byte over the limit tells a truncated sitemap from one of exactly max_size bytes
+        match GzDecoder::new(content).take(max_size.saturating_add(1)).read_to_end(&mut decoded) {
+            Ok(_) => {
+                if decoded.len() as u64 > max_size {
+                    decoded.truncate(max_size as usize);
+                    eprintln!("gzipped sitemap is bigger than {max_size} bytes once decompressed, truncated");
+                }
+                String::from_utf8_lossy(&decoded).to_string()
+            }
+            Err(_) => return Sitemap::UrlSet(Vec::new()),
+        }
+    } else {
+        String::from_utf8_lossy(content).to_string()
+    };
+
+    if xml.contains("<sitemapindex") {
+        Sitemap::Index(
+            blocks(&xml, "sitemap")
+                .into_iter()
+                .filter_map(|block| tag_value(block, "loc"))
+                .collect(),
+        )
+    } else {
+        Sitemap::UrlSet(
+            blocks(&xml, "url")
+                .into_iter()
+                .filter_map(|block| tag_value(block, "loc").map(|loc| (loc, tag_value(block, "lastmod"))))
+                .collect(),
+        )
+    }
+}
+
+// Inner content of every <tag>...</tag> element.
+fn blocks<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let mut ret = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(open.as_str()) {
+        let after = &rest[start + open.len()..];
+        // <url> or <url attr=...>, not <urlset>
+        if !after.starts_with(['>', ' ', '\t', '\r', '\n']) {
+            rest = after;
+            continue;
+        }
+        let content_start = match after.find('>') {
+            Some(pos) => pos + 1,
+            None => break,
+        };
+        let content = &after[content_start..];
+        match content.find(close.as_str()) {
+            Some(end) => {
+                ret.push(&content[..end]);
+                rest = &content[end + close.len()..];
+            }
+            None => break,
+        }
+    }
+    ret
+}
+
+fn tag_value(block: &str, tag: &str) -> Option<String> {
+    let value = blocks(block, tag).into_iter().next()?.trim();
+    let value = value
+        .strip_prefix("<![CDATA[")
+        .and_then(|value| value.strip_suffix("]]>"))
+        .unwrap_or(value)
+        .trim();
+    if value.is_empty() {
+        return None;
+    }
+    Some(
+        value
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"),
+    )
+}
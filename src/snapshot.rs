@@ -0,0 +1,99 @@
+use encoding_rs::{Encoding, UTF_8};
+use regex::bytes::Regex;
+
+// Body of an archive: transcoded to UTF-8 when it is text, untouched otherwise.
+pub enum Content {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl Content {
+    // Text content, binary archives have none.
+    pub fn text(self) -> String {
+        match self {
+            Content::Text(text) => text,
+            Content::Binary(_) => String::new(),
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Content::Text(text) => text.into_bytes(),
+            Content::Binary(bytes) => bytes,
+        }
+    }
+}
+
+// Decode the raw bytes of an archive with its Content-Type header (if any).
+pub fn decode(bytes: Vec<u8>, content_type: Option<&str>) -> Content {
+    // gzipped payloads (like sitemap.xml.gz) are binary whatever their Content-Type
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        return Content::Binary(bytes);
+    }
+    let mimetype = content_type
+        .and_then(|value| value.split(';').next())
+        .map(|mimetype| mimetype.trim().to_lowercase());
+    let text = match &mimetype {
+        Some(mimetype) if !mimetype.is_empty() => is_text_mimetype(mimetype),
+        // no usable header: a NUL byte near the start means binary
+        _ => !bytes.iter().take(1024).any(|byte| *byte == 0),
+    };
+    if !text {
+        return Content::Binary(bytes);
+    }
+
+    let encoding = match Encoding::for_bom(&bytes) {
+        Some((encoding, _)) => encoding,
+        None => content_type
+            .and_then(header_charset)
+            .or_else(|| declared_charset(&bytes))
+            .and_then(|label| Encoding::for_label(label.as_bytes()))
+            .unwrap_or(UTF_8),
+    };
+    // decode() strips the BOM and replaces the malformed sequences
+    let (decoded, _, _) = encoding.decode(&bytes);
+    Content::Text(decoded.into_owned())
+}
+
+fn is_text_mimetype(mimetype: &str) -> bool {
+    mimetype.starts_with("text/")
+        || mimetype.ends_with("+xml")
+        || mimetype.ends_with("+json")
+        || [
+            "application/json",
+            "application/javascript",
+            "application/x-javascript",
+            "application/ecmascript",
+            "application/xml",
+            "application/xhtml+xml",
+            "application/x-www-form-urlencoded",
+            "application/manifest+json",
+            "application/x-httpd-php",
+        ]
+        .contains(&mimetype)
+}
+
+// charset parameter of a Content-Type header
+fn header_charset(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if name.trim().eq_ignore_ascii_case("charset") {
+            Some(value.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+        } else {
+            None
+        }
+    })
+}
+
+// charset declared in the first bytes of the document: <meta charset>,
+// <meta http-equiv="Content-Type" content="...; charset=..."> or <?xml encoding?>
+fn declared_charset(bytes: &[u8]) -> Option<String> {
+    let head = &bytes[..bytes.len().min(1024)];
+    let declaration = Regex::new(
+        r#"(?i)<meta[^>]+charset\s*=\s*["']?([A-Za-z0-9_.:-]+)|<\?xml[^>]+encoding\s*=\s*["']([A-Za-z0-9_.:-]+)"#,
+    )
+    .unwrap();
+    let captures = declaration.captures(head)?;
+    let label = captures.get(1).or_else(|| captures.get(2))?;
+    Some(String::from_utf8_lossy(label.as_bytes()).to_string())
+}
@@ -0,0 +1,96 @@
+use reqwest::{Response, Url};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// Write the status line, headers and body of a live response to dir/host/path
+// and append the url -> file mapping to dir/index.txt.
+pub async fn store_response(dir: &Path, url: &str, response: Response) {
+    let relative_path = response_path(url);
+    let filepath = dir.join(&relative_path);
+
+    let mut content = format!("{:?} {}\n", response.version(), response.status()).into_bytes();
+    for (name, value) in response.headers() {
+        content.extend_from_slice(name.as_str().as_bytes());
+        content.extend_from_slice(b": ");
+        content.extend_from_slice(value.as_bytes());
+        content.push(b'\n');
+    }
+    content.push(b'\n');
+    match response.bytes().await {
+        Ok(body) => content.extend_from_slice(&body),
+        Err(e) => eprintln!("Error reading the body of {url}: {e}"),
+    }
+
+    if let Some(parent) = filepath.parent() {
+        fs::create_dir_all(parent).expect("Error creating the directory");
+    }
+    fs::write(&filepath, content).expect("Error writing the response to the file");
+
+    // one write per line so concurrent tasks don't interleave entries
+    let mut index = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join("index.txt"))
+        .expect("Error opening the index file");
+    index
+        .write_all(format!("{url} {path}\n", path = relative_path.display()).as_bytes())
+        .expect("Error writing to the index file");
+}
+
+// host/dir/.../name-<hash>, the hash of the full url keeps files with the same
+// path but a different query apart and never collides with a directory name.
+fn response_path(url: &str) -> PathBuf {
+    let mut path = PathBuf::new();
+    let mut name = String::from("index");
+    match Url::parse(url) {
+        Ok(parsed) => {
+            let host = match parsed.port() {
+                Some(port) => format!("{}_{port}", parsed.host_str().unwrap_or("nohost")),
+                None => parsed.host_str().unwrap_or("nohost").to_string(),
+            };
+            path.push(sanitize(&host));
+            let segments: Vec<&str> = parsed
+                .path_segments()
+                .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+                .unwrap_or_default();
+            if let Some((last, dirs)) = segments.split_last() {
+                for dir in dirs {
+                    path.push(sanitize(dir));
+                }
+                if !parsed.path().ends_with('/') {
+                    name = sanitize(last);
+                } else {
+                    path.push(sanitize(last));
+                }
+            }
+        }
+        Err(_) => path.push("invalid"),
+    }
+
+    let mut hasher = DefaultHasher::new();
+    url.hash(&mut hasher);
+    path.push(format!("{name}-{hash:08x}", hash = hasher.finish() as u32));
+    path
+}
+
+pub fn sanitize(segment: &str) -> String {
+    let sanitized: String = segment
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(100)
+        .collect();
+    if sanitized.is_empty() || sanitized.chars().all(|c| c == '.') {
+        "_".to_string()
+    } else {
+        sanitized
+    }
+}
@@ -0,0 +1,134 @@
+use futures::{stream, StreamExt, TryStreamExt};
+use reqwest::{Client, Url};
+use std::collections::BTreeMap;
+use tokio::net::lookup_host;
+use tokio_util::codec::{FramedRead, LinesCodec};
+use tokio_util::io::StreamReader;
+
+struct HostStats {
+    first_seen: String,
+    last_seen: String,
+    captures: usize,
+}
+
+// Hosts of a domain seen in the archive, with first/last capture timestamps
+// and the number of captures.
+pub struct Subdomains {
+    hosts: BTreeMap<String, HostStats>,
+    // host -> "dns=.. http=.. https=.." when the live check is enabled
+    live: BTreeMap<String, String>,
+}
+
+pub async fn get_subdomains(domain: &str, verbose: bool) -> Subdomains {
+    // hosts are compared lowercased and without trailing dot
+    let domain = domain.trim().trim_end_matches('/').trim_end_matches('.').to_lowercase();
+    if verbose {
+        println!("Looking for subdomains of {domain}...");
+    }
+    let url = format!(
+        "http://web.archive.org/cdx/search/cdx?url={domain}&matchType=domain&output=text&fl=timestamp,original"
+    );
+    let response = crate::cdx_request(&reqwest::Client::new(), url.as_str()).await;
+    let stream_reader = StreamReader::new(response.bytes_stream().map_err(std::io::Error::other));
+    let mut lines = FramedRead::new(stream_reader, LinesCodec::new());
+
+    let mut hosts: BTreeMap<String, HostStats> = BTreeMap::new();
+    let suffix = format!(".{domain}");
+    while let Some(line) = lines.next().await {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => continue,
+        };
+        let (timestamp, original) = match line.split_once(' ') {
+            Some(fields) => fields,
+            None => continue,
+        };
+        // CDX originals sometimes lack the scheme
+        let parsed = if original.contains("://") {
+            Url::parse(original)
+        } else {
+            Url::parse(format!("http://{original}").as_str())
+        };
+        let host = match parsed.as_ref().ok().and_then(|parsed| parsed.host_str()) {
+            Some(host) => host.trim_end_matches('.').to_lowercase(),
+            None => continue,
+        };
+        if host != domain && !host.ends_with(suffix.as_str()) {
+            continue;
+        }
+        let stats = hosts.entry(host).or_insert_with(|| HostStats {
+            first_seen: timestamp.to_string(),
+            last_seen: timestamp.to_string(),
+            captures: 0,
+        });
+        stats.captures += 1;
+        if timestamp < stats.first_seen.as_str() {
+            stats.first_seen = timestamp.to_string();
+        }
+        if timestamp > stats.last_seen.as_str() {
+            stats.last_seen = timestamp.to_string();
+        }
+    }
+    Subdomains {
+        hosts,
+        live: BTreeMap::new(),
+    }
+}
+
+impl Subdomains {
+    pub fn len(&self) -> usize {
+        self.hosts.len()
+    }
+
+    // Resolve every host and request it over http and https.
+    pub async fn check_live(&mut self, client: &Client, workers: usize) {
+        let mut results = stream::iter(self.hosts.keys().cloned())
+            .map(|host| async move {
+                let status = live_status(client, &host).await;
+                (host, status)
+            })
+            .buffer_unordered(workers);
+        while let Some((host, status)) = results.next().await {
+            self.live.insert(host, status);
+        }
+    }
+
+    // "host first_seen last_seen captures [live status]", or only the hosts.
+    pub fn to_text(&self, hosts_only: bool) -> String {
+        let mut ret = String::new();
+        for (host, stats) in &self.hosts {
+            if hosts_only {
+                ret.push_str(format!("{host}\n").as_str());
+                continue;
+            }
+            ret.push_str(
+                format!("{host} {} {} {}", stats.first_seen, stats.last_seen, stats.captures).as_str(),
+            );
+            if let Some(status) = self.live.get(host) {
+                ret.push(' ');
+                ret.push_str(status);
+            }
+            ret.push('\n');
+        }
+        ret
+    }
+}
+
+async fn live_status(client: &Client, host: &str) -> String {
+    let resolves = match lookup_host(format!("{host}:80")).await {
+        Ok(mut addrs) => addrs.next().is_some(),
+        Err(_) => false,
+    };
+    if !resolves {
+        return "dns=none".to_string();
+    }
+    let mut status = String::from("dns=ok");
+    for scheme in ["http", "https"] {
+        let code = match client.get(format!("{scheme}://{host}/")).send().await {
+            Ok(response) => response.status().as_u16().to_string(),
+            Err(_) => "-".to_string(),
+        };
+        status.push_str(format!(" {scheme}={code}").as_str());
+    }
+    status
+}
@@ -0,0 +1,122 @@
+use regex::Regex;
+use serde_json::Value;
+
+// Metadata files looked up in the archive of each domain.
+pub const FILES: [&str; 10] = [
+    "/.well-known/security.txt",
+    "/security.txt",
+    "/humans.txt",
+    "/crossdomain.xml",
+    "/clientaccesspolicy.xml",
+    "/.well-known/openid-configuration",
+    "/.well-known/apple-app-site-association",
+    "/apple-app-site-association",
+    "/.well-known/assetlinks.json",
+    "/manifest.json",
+];
+
+// (kind, value) pairs found in a metadata file: url, host, path, endpoint,
+// email, app, or the security.txt field name (policy, hiring...).
+pub fn extract(file: &str, content: &str) -> Vec<(String, String)> {
+    let mut found: Vec<(String, String)> = Vec::new();
+    let name = file.rsplit('/').next().unwrap_or(file);
+    match name {
+        "security.txt" => {
+            for line in content.lines() {
+                if let Some((field, value)) = line.split_once(':') {
+                    let field = field.trim().to_lowercase();
+                    let value = value.trim();
+                    match value.strip_prefix("mailto:") {
+                        Some(email) if field == "contact" => found.push(("email".to_string(), email.to_string())),
+                        _ if ["policy", "hiring", "acknowledgments", "acknowledgements", "canonical", "encryption", "contact"]
+                            .contains(&field.as_str()) =>
+                        {
+                            found.push((field, value.to_string()))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        "crossdomain.xml" => {
+            let domain = Regex::new(r#"(?i)<allow-(?:access|http-request-headers)-from[^>]*\sdomain\s*=\s*["']([^"']+)["']"#).unwrap();
+            for capture in domain.captures_iter(content) {
+                found.push(("host".to_string(), capture[1].to_string()));
+            }
+        }
+        "clientaccesspolicy.xml" => {
+            let domain = Regex::new(r#"(?i)<domain[^>]*\suri\s*=\s*["']([^"']+)["']"#).unwrap();
+            let resource = Regex::new(r#"(?i)<resource[^>]*\spath\s*=\s*["']([^"']+)["']"#).unwrap();
+            for capture in domain.captures_iter(content) {
+                found.push(("host".to_string(), capture[1].to_string()));
+            }
+            for capture in resource.captures_iter(content) {
+                found.push(("path".to_string(), capture[1].to_string()));
+            }
+        }
+        "openid-configuration" | "apple-app-site-association" | "assetlinks.json" | "manifest.json" => {
+            if let Ok(json) = serde_json::from_str::<Value>(content) {
+                extract_json(name, &json, "", &mut found);
+            }
+        }
+        _ => {}
+    }
+
+    // every file: plain urls and emails (humans.txt is mostly made of those)
+    let url = Regex::new(r#"https?://[^\s"'<>\\)]+"#).unwrap();
+    let email = Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap();
+    for m in url.find_iter(content) {
+        found.push(("url".to_string(), m.as_str().to_string()));
+    }
+    if name == "humans.txt" || name == "security.txt" {
+        for m in email.find_iter(content) {
+            found.push(("email".to_string(), m.as_str().to_string()));
+        }
+    }
+
+    let mut unique: Vec<(String, String)> = Vec::new();
+    for item in found {
+        if !unique.contains(&item) {
+            unique.push(item);
+        }
+    }
+    unique
+}
+
+fn extract_json(name: &str, json: &Value, key: &str, found: &mut Vec<(String, String)>) {
+    match json {
+        Value::Object(map) => {
+            for (child_key, value) in map {
+                extract_json(name, value, child_key, found);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                extract_json(name, item, key, found);
+            }
+        }
+        Value::String(value) => {
+            let kind = match (name, key) {
+                ("openid-configuration", _) if key.ends_with("_endpoint") || key == "jwks_uri" => Some("endpoint".to_string()),
+                ("openid-configuration", _) if value.starts_with("http") => Some("url".to_string()),
+                ("apple-app-site-association", "paths") | ("apple-app-site-association", "/") => Some("path".to_string()),
+                ("apple-app-site-association", "appID") | ("apple-app-site-association", "appIDs") => Some("app".to_string()),
+                ("assetlinks.json", "package_name") => Some("app".to_string()),
+                ("assetlinks.json", "site") => Some("url".to_string()),
+                ("manifest.json", "start_url") | ("manifest.json", "scope") | ("manifest.json", "src") | ("manifest.json", "url") => {
+                    if value.starts_with("http") {
+                        Some("url".to_string())
+                    } else {
+                        Some("path".to_string())
+                    }
+                }
+                ("manifest.json", "id") if value.contains('.') => Some("app".to_string()),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                found.push((kind, value.to_string()));
+            }
+        }
+        _ => {}
+    }
+}
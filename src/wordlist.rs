@@ -0,0 +1,89 @@
+use reqwest::Url;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Unique words of archived urls with the number of urls they appear in.
+#[derive(Default)]
+pub struct Wordlists {
+    directories: HashMap<String, usize>,
+    filenames: HashMap<String, usize>,
+    extensions: HashMap<String, usize>,
+    parameters: HashMap<String, usize>,
+    values: HashMap<String, usize>,
+}
+
+impl Wordlists {
+    pub fn add_url(&mut self, url: &str) {
+        let parsed = match Url::parse(url) {
+            Ok(parsed) => parsed,
+            Err(_) => return,
+        };
+        let segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
+        if let Some((last, dirs)) = segments.split_last() {
+            for dir in dirs {
+                add_word(&mut self.directories, dir);
+            }
+            match last.rsplit_once('.') {
+                Some((stem, extension)) if !stem.is_empty() && !extension.is_empty() => {
+                    add_word(&mut self.filenames, last);
+                    add_word(&mut self.extensions, extension);
+                }
+                _ => add_word(&mut self.directories, last),
+            }
+        }
+        for (name, value) in parsed.query_pairs() {
+            add_word(&mut self.parameters, &name);
+            add_word(&mut self.values, &value);
+        }
+    }
+
+    fn lists(&self) -> [(&str, &HashMap<String, usize>); 5] {
+        [
+            ("directories", &self.directories),
+            ("filenames", &self.filenames),
+            ("extensions", &self.extensions),
+            ("parameters", &self.parameters),
+            ("values", &self.values),
+        ]
+    }
+
+    // Write one <list>.txt file per list in dir.
+    pub fn write_to_dir(&self, dir: &Path, ranked: bool) {
+        fs::create_dir_all(dir).expect("Error creating the directory");
+        for (name, words) in self.lists() {
+            // an empty list is an empty file, not a blank word
+            let content: String = sorted_words(words, ranked).iter().map(|word| format!("{word}\n")).collect();
+            fs::write(dir.join(format!("{name}.txt")), content).expect("Error writing the wordlist");
+        }
+    }
+
+    pub fn to_text(&self, ranked: bool) -> String {
+        let mut ret = String::new();
+        for (name, words) in self.lists() {
+            ret.push_str(format!("== {name} ({len})\n", len = words.len()).as_str());
+            for word in sorted_words(words, ranked) {
+                ret.push_str(format!("{word}\n").as_str());
+            }
+        }
+        ret
+    }
+}
+
+fn add_word(words: &mut HashMap<String, usize>, word: &str) {
+    let word = word.trim();
+    if !word.is_empty() && !word.contains(char::is_whitespace) {
+        *words.entry(word.to_string()).or_insert(0) += 1;
+    }
+}
+
+// Most frequent first when ranked, alphabetical otherwise.
+fn sorted_words(words: &HashMap<String, usize>, ranked: bool) -> Vec<&str> {
+    let mut sorted: Vec<(&String, &usize)> = words.iter().collect();
+    if ranked {
+        sorted.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    } else {
+        sorted.sort();
+    }
+    sorted.into_iter().map(|(word, _)| word.as_str()).collect()
+}